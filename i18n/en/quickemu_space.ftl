@@ -6,3 +6,4 @@ page-id = Page { $num }
 git-description = Git commit {$hash} on {$date}
 page-newvm = Create new VM
 default-vm-dir = Default VM directory
+page-library = VM Library
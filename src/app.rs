@@ -3,13 +3,13 @@
 use crate::config::Config;
 use crate::creation;
 use crate::fl;
+use crate::library;
 use ashpd::desktop::file_chooser::SelectedFiles;
 use cosmic::app::{context_drawer, Core, Task};
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::iced::{Alignment, Subscription};
 use cosmic::widget::{self, icon, menu, nav_bar};
 use cosmic::{cosmic_theme, theme, Application, ApplicationExt, Element};
-use futures_util::SinkExt;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -32,6 +32,7 @@ pub struct AppModel {
     config: Config,
     page: Page,
     creation: creation::State,
+    library: library::State,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -41,11 +42,12 @@ pub enum Message {
     UpdateDefaultVMDir(PathBuf),
     SelectDefaultVMDir,
     Creation(creation::Message),
+    Library(library::Message),
     OpenRepositoryUrl,
-    SubscriptionChannel,
     ToggleContextPage(ContextPage),
     UpdateConfig(Config),
     LaunchUrl(String),
+    CopyToClipboard(String),
 }
 
 /// Create a COSMIC application from the app model
@@ -81,9 +83,14 @@ impl Application for AppModel {
             .icon(icon::from_name("applications-science-symbolic"))
             .activate();
 
+        nav.insert()
+            .text(fl!("page-library"))
+            .data::<Page>(Page::Library)
+            .icon(icon::from_name("computer-symbolic"));
+
         let (creation, creation_task) = creation::State::new();
 
-        let (config_handler, config) =
+        let (config_handler, mut config) =
             match cosmic_config::Config::new(Self::APP_ID, Config::VERSION) {
                 Ok(config_handler) => {
                     let config = match Config::get_entry(&config_handler) {
@@ -104,6 +111,8 @@ impl Application for AppModel {
                 }
             };
 
+        let (library, library_task) = library::State::new(&mut config, config_handler.as_ref());
+
         // Construct the app model with the runtime's core.
         let mut app = AppModel {
             core,
@@ -114,12 +123,13 @@ impl Application for AppModel {
             key_binds: HashMap::new(),
             page: Page::default(),
             creation,
+            library,
         };
 
         // Create a startup command that sets the window title.
         let command = app.update_title();
 
-        let task = Task::batch([creation_task, command]);
+        let task = Task::batch([creation_task, library_task, command]);
         (app, task)
     }
 
@@ -169,7 +179,8 @@ impl Application for AppModel {
     /// events received by widgets will be passed to the update method.
     fn view(&self) -> Element<Self::Message> {
         match self.page {
-            Page::Creation => self.creation.view(),
+            Page::Creation => self.creation.view(&self.config),
+            Page::Library => self.library.view(),
         }
     }
 
@@ -179,18 +190,11 @@ impl Application for AppModel {
     /// emit messages to the application through a channel. They are started at the
     /// beginning of the application, and persist through its lifetime.
     fn subscription(&self) -> Subscription<Self::Message> {
-        struct MySubscription;
-
         Subscription::batch(vec![
-            // Create a subscription which emits updates through a channel.
-            Subscription::run_with_id(
-                std::any::TypeId::of::<MySubscription>(),
-                cosmic::iced::stream::channel(4, move |mut channel| async move {
-                    _ = channel.send(Message::SubscriptionChannel).await;
-
-                    futures_util::future::pending().await
-                }),
-            ),
+            // Track the state of VMs launched from the library.
+            self.library.subscription(),
+            // Navigate the OS search results with the keyboard.
+            self.creation.subscription(),
             // Watch for application configuration changes.
             self.core()
                 .watch_config::<Config>(Self::APP_ID)
@@ -234,10 +238,6 @@ impl Application for AppModel {
                 _ = open::that_detached(REPOSITORY);
             }
 
-            Message::SubscriptionChannel => {
-                // For example purposes only.
-            }
-
             Message::ToggleContextPage(context_page) => {
                 if self.context_page == context_page {
                     // Close the context drawer if the toggled context page is the same.
@@ -250,7 +250,12 @@ impl Application for AppModel {
             }
 
             Message::UpdateConfig(config) => {
+                let refresh_library = self.config.existing_vm_configs != config.existing_vm_configs
+                    || self.config.vm_metadata != config.vm_metadata;
                 self.config = config;
+                if refresh_library {
+                    return self.library.refresh(&self.config);
+                }
             }
 
             Message::LaunchUrl(url) => match open::that_detached(&url) {
@@ -260,11 +265,26 @@ impl Application for AppModel {
                 }
             },
 
+            Message::CopyToClipboard(text) => return cosmic::iced::clipboard::write(text),
+
             Message::Creation(msg) => {
                 return self
                     .creation
                     .update(msg, &mut self.config, self.config_handler.as_ref());
             }
+
+            Message::Library(msg) => {
+                // VMs launched from elsewhere in the app report their status in the library
+                let show_library = matches!(msg, library::Message::StartVM(_))
+                    && !matches!(self.page, Page::Library);
+                let task = self
+                    .library
+                    .update(msg, &mut self.config, self.config_handler.as_ref());
+                if show_library {
+                    return Task::batch([task, self.activate_page(Page::Library)]);
+                }
+                return task;
+            }
         }
         Task::none()
     }
@@ -274,7 +294,18 @@ impl Application for AppModel {
         // Activate the page in the model.
         self.nav.activate(id);
 
-        self.update_title()
+        let title_task = self.update_title();
+        match self.nav.data::<Page>(id).copied() {
+            Some(Page::Library) => {
+                self.page = Page::Library;
+                Task::batch([self.library.refresh(&self.config), title_task])
+            }
+            Some(page) => {
+                self.page = page;
+                title_task
+            }
+            None => title_task,
+        }
     }
 }
 
@@ -338,6 +369,18 @@ impl AppModel {
         .into()
     }
 
+    /// Activates the nav bar item displaying the given page.
+    fn activate_page(&mut self, page: Page) -> Task<Message> {
+        let id = self
+            .nav
+            .iter()
+            .find(|&id| self.nav.data::<Page>(id) == Some(&page));
+        match id {
+            Some(id) => self.on_nav_select(id),
+            None => Task::none(),
+        }
+    }
+
     /// Updates the header and window titles.
     pub fn update_title(&mut self) -> Task<Message> {
         let mut window_title = fl!("app-title");
@@ -355,10 +398,11 @@ impl AppModel {
     }
 }
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub enum Page {
     #[default]
     Creation,
+    Library,
 }
 
 /// The context page to display in the context drawer.
@@ -405,3 +449,22 @@ pub(crate) async fn select_dir() -> Option<PathBuf> {
             .and_then(|file| file.to_file_path().ok())
     })
 }
+
+pub(crate) async fn select_file(title: &str) -> Option<PathBuf> {
+    let result = SelectedFiles::open_file()
+        .title(title)
+        .accept_label("Select")
+        .modal(true)
+        .multiple(false)
+        .send()
+        .await
+        .unwrap()
+        .response();
+
+    result.ok().and_then(|file| {
+        file.uris()
+            .iter()
+            .next()
+            .and_then(|file| file.to_file_path().ok())
+    })
+}
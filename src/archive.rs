@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Portable VM archives: a gzip compressed tarball holding a VM's config and directory, along
+//! with a manifest describing them.
+//!
+//! The manifest is always the first entry, followed by the config file and then the contents of
+//! the VM directory, stored under their original names.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::vm::{Vm, VmFileKind};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    pub format_version: u32,
+    pub vm_name: String,
+    pub guest: String,
+    /// RFC 3339 timestamp of the export.
+    pub exported: String,
+    pub includes_installer_media: bool,
+    /// Name of the config file within the archive.
+    pub config: String,
+    /// Name of the VM directory within the archive.
+    pub dir: String,
+    /// Where the VM directory was located on the exporting machine. Paths in the config which
+    /// point into it are rewritten on import.
+    pub original_dir: PathBuf,
+}
+
+/// Packs the VM into an archive at the destination path. Installer media is left out unless
+/// requested, since it can usually be downloaded again. Linked clones are stored flattened.
+pub fn export(vm: &Vm, destination: &Path, include_installer_media: bool) -> Result<(), String> {
+    if destination.exists() {
+        return Err(format!("{} already exists", destination.display()));
+    }
+    let result = write_archive(vm, destination, include_installer_media);
+    if result.is_err() {
+        // Don't leave a truncated archive behind
+        let _ = std::fs::remove_file(destination);
+    }
+    result
+}
+
+fn write_archive(vm: &Vm, destination: &Path, include_installer_media: bool) -> Result<(), String> {
+    let dir = vm.dir();
+    let dir_name = file_name(&dir)?;
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        vm_name: vm.name().to_string(),
+        guest: vm.config().guest.to_string(),
+        exported: chrono::Local::now().to_rfc3339(),
+        includes_installer_media: include_installer_media,
+        config: file_name(vm.config_path())?,
+        dir: dir_name.clone(),
+        original_dir: dir.clone(),
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+
+    // Media left out of the archive mustn't be referenced by the imported config
+    let mut exported_vm = vm.clone();
+    if !include_installer_media {
+        let in_dir = |iso: &Path| dir.join(iso).starts_with(&dir);
+        if exported_vm.installer_iso().is_some_and(in_dir) {
+            exported_vm.set_installer_iso(None);
+        }
+        if exported_vm.secondary_iso().is_some_and(in_dir) {
+            exported_vm.set_secondary_iso(None);
+        }
+    }
+    let config_toml = exported_vm.config_toml()?;
+
+    let file = File::create(destination)
+        .map_err(|e| format!("Couldn't create {}: {e}", destination.display()))?;
+    let mut builder =
+        tar::Builder::new(GzEncoder::new(BufWriter::new(file), Compression::default()));
+    let write_error = |e: std::io::Error| format!("Couldn't write archive: {e}");
+
+    let mut append_data = |name: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        builder.append_data(&mut header, name, data)
+    };
+    append_data(MANIFEST_NAME, &manifest_json).map_err(write_error)?;
+    append_data(&manifest.config, config_toml.as_bytes()).map_err(write_error)?;
+
+    for file in vm.files().map_err(|e| e.to_string())? {
+        let name = match file.kind {
+            VmFileKind::Config => continue,
+            VmFileKind::InstallerMedia if !include_installer_media => continue,
+            _ => match file.path.strip_prefix(&dir) {
+                Ok(relative) => Path::new(&dir_name).join(relative),
+                // Files outside of the VM directory can't be restored to a sensible location
+                Err(_) => continue,
+            },
+        };
+        let flattened = match file.kind {
+            VmFileKind::DiskImage => flatten_overlay(&file.path, &dir, destination)?,
+            _ => None,
+        };
+        let appended = builder
+            .append_path_with_name(flattened.as_ref().unwrap_or(&file.path), &name)
+            .map_err(|e| format!("Couldn't add {} to archive: {e}", file.path.display()));
+        if let Some(flattened) = flattened {
+            let _ = std::fs::remove_file(flattened);
+        }
+        appended?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .and_then(|mut writer| writer.flush())
+        .map_err(write_error)
+}
+
+/// Linked clones are overlays of another VM's disk images, which won't exist wherever the archive
+/// is imported. Such images are merged with their backing files into a temporary copy next to
+/// the archive, which is returned so that it can be added in their place.
+fn flatten_overlay(image: &Path, dir: &Path, archive: &Path) -> Result<Option<PathBuf>, String> {
+    let info = crate::qemu_img::info(image)?;
+    match info.full_backing_filename {
+        Some(backing_file) if !backing_file.starts_with(dir) => {
+            let mut flattened = archive.as_os_str().to_owned();
+            flattened.push(format!(".{}.tmp", file_name(image)?));
+            let flattened = PathBuf::from(flattened);
+            if let Err(e) = crate::qemu_img::flatten(image, &info.format, &flattened) {
+                let _ = std::fs::remove_file(&flattened);
+                return Err(format!("Couldn't flatten {}: {e}", image.display()));
+            }
+            Ok(Some(flattened))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Reads the manifest from the start of an archive without unpacking anything else.
+pub fn read_manifest(archive: &Path) -> Result<Manifest, String> {
+    let mut archive = open(archive)?;
+    let mut entries = archive.entries().map_err(read_error)?;
+    manifest(&mut entries)
+}
+
+/// Unpacks an archive into the parent directory, returning the imported VM. Paths within its
+/// config are updated to match the new location.
+pub fn import(archive: &Path, parent: &Path) -> Result<Vm, String> {
+    let mut archive = open(archive)?;
+    let mut entries = archive.entries().map_err(read_error)?;
+    let manifest = manifest(&mut entries)?;
+
+    let config_path = parent.join(&manifest.config);
+    let dir = parent.join(&manifest.dir);
+    for path in [&config_path, &dir] {
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+    }
+
+    let result = (|| {
+        for entry in entries {
+            let mut entry = entry.map_err(read_error)?;
+            let path = entry.path().map_err(read_error)?.into_owned();
+            if path != Path::new(&manifest.config) && !path.starts_with(&manifest.dir) {
+                return Err(format!("Unexpected file in archive: {}", path.display()));
+            }
+            entry
+                .unpack_in(parent)
+                .map_err(|e| format!("Couldn't unpack {}: {e}", path.display()))?;
+        }
+
+        let mut vm = Vm::load(config_path.clone())?;
+        vm.rebase_dir(&manifest.original_dir, &dir);
+        vm.save()?;
+        Ok(vm)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&config_path);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    result
+}
+
+type Entries<'a> = tar::Entries<'a, GzDecoder<BufReader<File>>>;
+
+fn open(path: &Path) -> Result<tar::Archive<GzDecoder<BufReader<File>>>, String> {
+    let file = File::open(path).map_err(|e| format!("Couldn't open {}: {e}", path.display()))?;
+    Ok(tar::Archive::new(GzDecoder::new(BufReader::new(file))))
+}
+
+fn manifest(entries: &mut Entries<'_>) -> Result<Manifest, String> {
+    let mut entry = entries
+        .next()
+        .ok_or_else(|| "The archive is empty".to_string())?
+        .map_err(read_error)?;
+    if entry.path().map_err(read_error)?.as_ref() != Path::new(MANIFEST_NAME) {
+        return Err("The archive wasn't exported from Quickemu Space".to_string());
+    }
+
+    let mut manifest = String::new();
+    entry.read_to_string(&mut manifest).map_err(read_error)?;
+    let manifest: Manifest =
+        serde_json::from_str(&manifest).map_err(|e| format!("Invalid manifest: {e}"))?;
+
+    if manifest.format_version > FORMAT_VERSION {
+        return Err("The archive was exported by a newer version of Quickemu Space".to_string());
+    }
+    // The config and directory are unpacked by name, so they mustn't be able to escape the
+    // destination directory
+    if !is_plain_name(&manifest.config) || !is_plain_name(&manifest.dir) {
+        return Err("Invalid manifest: unsafe file names".to_string());
+    }
+    Ok(manifest)
+}
+
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+fn file_name(path: &Path) -> Result<String, String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("{} has no file name", path.display()))
+}
+
+fn read_error(e: std::io::Error) -> String {
+    format!("Couldn't read archive: {e}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn write_archive_with_manifest(name: &str, manifest: &serde_json::Value) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "quickemu-space-{name}-{}.tar.gz",
+            std::process::id()
+        ));
+        let json = serde_json::to_vec(manifest).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&path).unwrap(),
+            Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(json.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, MANIFEST_NAME, json.as_slice())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    fn manifest_json(config: &str, dir: &str, format_version: u32) -> serde_json::Value {
+        serde_json::json!({
+            "format-version": format_version,
+            "vm-name": "vm",
+            "guest": "linux",
+            "exported": "2024-01-01T00:00:00+00:00",
+            "includes-installer-media": false,
+            "config": config,
+            "dir": dir,
+            "original-dir": "/home/user/vms/vm",
+        })
+    }
+
+    #[test]
+    fn plain_names_are_single_components() {
+        assert!(is_plain_name("vm.toml"));
+        assert!(is_plain_name("vm"));
+        assert!(!is_plain_name(""));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name("../vm"));
+        assert!(!is_plain_name("/etc"));
+        assert!(!is_plain_name("vms/vm"));
+    }
+
+    #[test]
+    fn valid_manifests_are_read() {
+        let path = write_archive_with_manifest("valid", &manifest_json("vm.toml", "vm", 1));
+        let manifest = read_manifest(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let manifest = manifest.unwrap();
+        assert_eq!(manifest.config, "vm.toml");
+        assert_eq!(manifest.dir, "vm");
+    }
+
+    #[test]
+    fn manifests_escaping_the_destination_are_rejected() {
+        let path = write_archive_with_manifest("unsafe", &manifest_json("../vm.toml", "vm", 1));
+        let manifest = read_manifest(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(manifest.is_err());
+    }
+
+    #[test]
+    fn manifests_from_newer_versions_are_rejected() {
+        let path = write_archive_with_manifest(
+            "newer",
+            &manifest_json("vm.toml", "vm", FORMAT_VERSION + 1),
+        );
+        let manifest = read_manifest(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(manifest.is_err());
+    }
+
+    /// Exports a VM with an installer ISO inside its directory and a driver ISO outside of it,
+    /// then imports it elsewhere.
+    fn round_trip(name: &str, include_installer_media: bool) -> (PathBuf, Vm) {
+        let parent = temp_dir(name);
+        let vm_dir = parent.join("exported").join("vm");
+        std::fs::create_dir_all(&vm_dir).unwrap();
+        std::fs::write(vm_dir.join("installer.iso"), b"iso").unwrap();
+
+        let mut vm = Vm::with_default_config(parent.join("exported").join("vm.toml"));
+        vm.set_installer_iso(Some(vm_dir.join("installer.iso")));
+        vm.set_secondary_iso(Some(PathBuf::from("/elsewhere/drivers.iso")));
+        vm.save().unwrap();
+
+        let archive = parent.join("vm.tar.gz");
+        export(&vm, &archive, include_installer_media).unwrap();
+        let imported_dir = parent.join("imported");
+        std::fs::create_dir(&imported_dir).unwrap();
+        let imported = import(&archive, &imported_dir).unwrap();
+        (parent, imported)
+    }
+
+    #[test]
+    fn excluded_media_is_detached_on_import() {
+        let (parent, vm) = round_trip("export-without-media", false);
+
+        assert_eq!(vm.installer_iso(), None);
+        assert_eq!(
+            vm.secondary_iso(),
+            Some(Path::new("/elsewhere/drivers.iso"))
+        );
+        assert!(!vm.dir().join("installer.iso").exists());
+        // The config on disk matches what was returned
+        let reloaded = Vm::load(vm.config_path().to_owned()).unwrap();
+        assert_eq!(reloaded.installer_iso(), None);
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+
+    #[test]
+    fn included_media_is_rebased_on_import() {
+        let (parent, vm) = round_trip("export-with-media", true);
+
+        let iso = parent.join("imported").join("vm").join("installer.iso");
+        assert_eq!(vm.installer_iso(), Some(iso.as_path()));
+        assert!(iso.is_file());
+        assert_eq!(
+            vm.secondary_iso(),
+            Some(Path::new("/elsewhere/drivers.iso"))
+        );
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! A copy of the quickget OS catalog kept in the cache directory, so that VMs can still be created
+//! without a network connection.
+
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use cosmic::Application;
+use quickget_core::{data_structures::OS, ConfigSearch};
+use serde::{Deserialize, Serialize};
+
+const CACHE_FILE_NAME: &str = "os_catalog.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Catalog {
+    /// RFC 3339 timestamp of when the catalog was fetched.
+    pub fetched: String,
+    pub os_list: Vec<OS>,
+}
+
+impl Catalog {
+    /// The date the catalog was fetched, formatted for display.
+    pub fn fetched_date(&self) -> String {
+        chrono::DateTime::parse_from_rfc3339(&self.fetched)
+            .map(|date| {
+                date.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.fetched.clone())
+    }
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(crate::app::AppModel::APP_ID).join(CACHE_FILE_NAME))
+}
+
+/// Reads the cached catalog, if one has been saved.
+pub fn load() -> Option<Catalog> {
+    let file = File::open(cache_path()?).ok()?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .inspect_err(|e| eprintln!("failed to read cached OS catalog: {e}"))
+        .ok()
+}
+
+fn save(catalog: &Catalog) -> Result<(), String> {
+    let path = cache_path().ok_or("Couldn't determine the cache directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // Written to a temporary file first, so that an interrupted write can't corrupt the cache
+    let tmp_path = path.with_extension("json.tmp");
+    let file = File::create(&tmp_path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, catalog).map_err(|e| e.to_string())?;
+    // Dropping the writer would ignore errors while flushing, leaving a truncated cache
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .and_then(|file| file.sync_all())
+        .map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+}
+
+/// Fetches the latest catalog, and caches it for the next launch.
+pub async fn fetch() -> Result<Catalog, String> {
+    let os_list = ConfigSearch::new()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_os_list();
+    let catalog = Catalog {
+        fetched: chrono::Local::now().to_rfc3339(),
+        os_list,
+    };
+
+    let cached = catalog.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = save(&cached) {
+            eprintln!("failed to cache OS catalog: {e}");
+        }
+    })
+    .await
+    .expect("Couldn't spawn thread");
+
+    Ok(catalog)
+}
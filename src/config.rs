@@ -1,12 +1,62 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+use crate::vm::Preallocation;
 
 #[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
 pub struct Config {
     pub default_vm_dir: PathBuf,
     pub existing_vm_configs: Vec<PathBuf>,
+    /// Notes, tags, icons and releases of registered VMs, keyed by config path.
+    pub vm_metadata: BTreeMap<PathBuf, VmMetadata>,
+    pub creation_presets: Vec<CreationPreset>,
+}
+
+/// Details about a VM which only matter to the user, so they aren't stored in its config.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VmMetadata {
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub icon: Option<String>,
+    /// The quickget release the VM was created from. Unknown for VMs created elsewhere.
+    pub release: Option<String>,
+    pub edition: Option<String>,
+}
+
+impl VmMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+            && self.tags.is_empty()
+            && self.icon.is_none()
+            && self.release.is_none()
+            && self.edition.is_none()
+    }
+}
+
+/// Options of the creation wizard saved under a name, so that the same kind of VM can be created
+/// again in one click.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CreationPreset {
+    pub name: String,
+    pub os: String,
+    /// A release of the OS, or [`LATEST_RELEASE`] for whichever is newest when the preset is used.
+    pub release: String,
+    pub edition: Option<String>,
+    pub arch: String,
+    pub cpu_cores: usize,
+    pub ram: u64,
+    pub directory: PathBuf,
+    pub shared_dir: Option<PathBuf>,
+    pub disk_size: Option<u64>,
+    pub preallocation: Option<Preallocation>,
+    pub uefi: Option<bool>,
+    pub secure_boot: Option<bool>,
+    pub tpm: Option<bool>,
 }
+
+pub const LATEST_RELEASE: &str = "latest";
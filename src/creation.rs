@@ -1,50 +1,126 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod docker;
 mod download;
-mod options;
+pub(crate) mod options;
 
-use std::{fmt::Display, path::PathBuf};
+use std::{fmt::Display, path::PathBuf, sync::LazyLock};
 
 use cosmic::{
     app::command::Task,
     cosmic_config,
     iced::{
         alignment::{Horizontal, Vertical},
-        Length,
+        keyboard::{self, key::Named, Key},
+        widget::scrollable::{self, RelativeOffset},
+        Alignment, Length, Subscription,
     },
     theme,
     widget::{self, combo_box, icon},
     Apply, Element,
 };
-use quickget_core::{data_structures::OS, ConfigSearch};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use quickget_core::data_structures::OS;
 
-use crate::config::Config;
+use crate::{
+    catalog::{self, Catalog},
+    config::{Config, CreationPreset, VmMetadata},
+};
+
+static SEARCH_INPUT_ID: LazyLock<widget::Id> = LazyLock::new(|| widget::Id::new("os-search"));
+static OS_LIST_ID: LazyLock<widget::Id> = LazyLock::new(|| widget::Id::new("os-list"));
 
 pub struct State {
     os_list: Vec<OS>,
+    /// When the OS list was fetched from quickget, formatted for display.
+    catalog_date: Option<String>,
+    refreshing_catalog: bool,
+    catalog_error: Option<String>,
+    search: String,
+    /// Index into the search results of the OS which Enter selects.
+    highlighted: usize,
+    /// Release and edition of the VM being created, recorded in its metadata once it exists.
+    creating_release: Option<(String, Option<String>)>,
+    /// Why the preset at this index couldn't be used.
+    preset_issue: Option<(usize, String)>,
     page: Page,
 }
 
 impl State {
     pub fn new() -> (Self, Task<crate::app::Message>) {
-        let task = Task::perform(
-            async { ConfigSearch::new().await.map(|x| x.into_os_list()) },
-            |x| {
-                match x {
-                    Ok(os_list) => crate::app::Message::Creation(Message::OSList(os_list)),
-                    Err(e) => crate::app::Message::Creation(Message::Error(e.to_string())),
-                }
-                .into()
+        // The cached catalog is shown straight away, while the latest one is fetched
+        let load_cached = Task::perform(
+            async {
+                tokio::task::spawn_blocking(catalog::load)
+                    .await
+                    .expect("Couldn't spawn thread")
             },
+            |catalog| crate::app::Message::Creation(Message::CachedCatalog(catalog)).into(),
         );
+        let task = Task::batch([load_cached, refresh_catalog()]);
         (
             Self {
                 os_list: vec![],
+                catalog_date: None,
+                refreshing_catalog: true,
+                catalog_error: None,
+                search: String::new(),
+                highlighted: 0,
+                creating_release: None,
+                preset_issue: None,
                 page: Page::default(),
             },
             task,
         )
     }
+    /// Moves through the search results with the arrow keys while an OS is being chosen.
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        if !matches!(self.page, Page::SelectOS) {
+            return Subscription::none();
+        }
+        keyboard::on_key_press(|key, _modifiers| match key {
+            Key::Named(Named::ArrowDown) => Some(Message::MoveHighlight(1).into()),
+            Key::Named(Named::ArrowUp) => Some(Message::MoveHighlight(-1).into()),
+            _ => None,
+        })
+    }
+
+    /// The OSes matching the search, best matches first.
+    fn search_results(&self) -> Vec<&OS> {
+        let search = self.search.trim();
+        if search.is_empty() {
+            return self.os_list.iter().collect();
+        }
+        let matcher = SkimMatcherV2::default();
+        let mut results: Vec<_> = self
+            .os_list
+            .iter()
+            .filter_map(|os| {
+                let score = [&os.pretty_name, &os.name]
+                    .into_iter()
+                    .filter_map(|name| matcher.fuzzy_match(name, search))
+                    .max()?;
+                Some((score, os))
+            })
+            .collect();
+        // The sort is stable, so equally good matches keep their order from the catalog
+        results.sort_by(|(a, _), (b, _)| b.cmp(a));
+        results.into_iter().map(|(_, os)| os).collect()
+    }
+
+    fn set_catalog(&mut self, catalog: Catalog) -> Task<crate::app::Message> {
+        self.catalog_date = Some(catalog.fetched_date());
+        self.os_list = catalog.os_list;
+        self.highlighted = 0;
+        self.preset_issue = None;
+        // A background refresh shouldn't move the user away from what they're doing
+        if matches!(self.page, Page::Loading | Page::Error(_)) {
+            self.page = Page::SelectOS;
+            return widget::text_input::focus(SEARCH_INPUT_ID.clone());
+        }
+        Task::none()
+    }
+
     pub fn update(
         &mut self,
         msg: Message,
@@ -52,16 +128,153 @@ impl State {
         config_handler: Option<&cosmic_config::Config>,
     ) -> Task<crate::app::Message> {
         match msg {
-            Message::OSList(os_list) => {
-                self.os_list = os_list;
-                self.page = Page::SelectOS;
+            Message::CachedCatalog(catalog) => {
+                // A freshly fetched catalog may have arrived first
+                if let Some(catalog) = catalog.filter(|_| self.catalog_date.is_none()) {
+                    return self.set_catalog(catalog);
+                }
+            }
+            Message::RefreshCatalog => {
+                if !self.refreshing_catalog {
+                    self.refreshing_catalog = true;
+                    self.catalog_error = None;
+                    return refresh_catalog();
+                }
+            }
+            Message::CatalogRefreshed(result) => {
+                self.refreshing_catalog = false;
+                match result {
+                    Ok(catalog) => return self.set_catalog(catalog),
+                    // Without a cached catalog, there's nothing which can be shown
+                    Err(e) if self.catalog_date.is_none() => {
+                        self.page = Page::Error(format!("Couldn't fetch the OS catalog: {e}"));
+                    }
+                    Err(e) => self.catalog_error = Some(e),
+                }
+            }
+            Message::Search(search) => {
+                self.search = search;
+                self.highlighted = 0;
+                return scrollable::snap_to(OS_LIST_ID.clone(), RelativeOffset::START);
+            }
+            Message::MoveHighlight(offset) => {
+                let results = self.search_results().len();
+                if results == 0 {
+                    return Task::none();
+                }
+                self.highlighted = self
+                    .highlighted
+                    .saturating_add_signed(offset)
+                    .min(results - 1);
+                // Rows are all the same height, so this keeps the highlighted OS in view
+                let y = self.highlighted as f32 / (results - 1).max(1) as f32;
+                return scrollable::snap_to(OS_LIST_ID.clone(), RelativeOffset { x: 0.0, y });
+            }
+            Message::SelectHighlighted => {
+                if let Some(os) = self.search_results().get(self.highlighted) {
+                    let os = (*os).clone();
+                    return self.update(Message::SelectedOS(os), config, config_handler);
+                }
+            }
+            Message::ApplyPreset(index) => {
+                let Some(preset) = config.creation_presets.get(index) else {
+                    return Task::none();
+                };
+                // The problem is shown alongside the preset, so that another one can be picked
+                let Some(os) = self.os_list.iter().find(|os| os.name == preset.os) else {
+                    self.preset_issue =
+                        Some((index, format!("{} is no longer in the catalog", preset.os)));
+                    return Task::none();
+                };
+                match options::OptionSelection::from_preset(os.clone(), preset) {
+                    Ok(options) => {
+                        self.preset_issue = None;
+                        self.page = Page::Options(options);
+                    }
+                    Err(e) => self.preset_issue = Some((index, e)),
+                }
+            }
+            Message::DeletePreset(index) => {
+                self.preset_issue = None;
+                let mut presets = config.creation_presets.clone();
+                if index < presets.len() {
+                    presets.remove(index);
+                    set_presets(config, config_handler, presets);
+                }
             }
+            Message::SelectCustomImage => {
+                return Task::perform(
+                    crate::app::select_file("Select ISO or Disk Image"),
+                    |image| {
+                        match image {
+                            Some(image) => {
+                                crate::app::Message::Creation(Message::SelectedCustomImage(image))
+                            }
+                            None => crate::app::Message::None,
+                        }
+                        .into()
+                    },
+                );
+            }
+            Message::SelectedCustomImage(image) => {
+                self.page = Page::Options(options::OptionSelection::new_custom(
+                    image,
+                    config.default_vm_dir.clone().into(),
+                ));
+            }
+            Message::CreateFromImage(vm_name) => match self.page {
+                Page::Options(ref options) => {
+                    let options = options.clone();
+                    self.creating_release = None;
+                    self.page = Page::Finalizing;
+                    return Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || options.create_from_image(&vm_name))
+                                .await
+                                .expect("Couldn't spawn thread")
+                        },
+                        |result| {
+                            crate::app::Message::Creation(match result {
+                                Ok(config_path) => Message::FinalizedConfigPath(config_path),
+                                Err(e) => Message::Error(format!("Error creating VM: {e}")),
+                            })
+                            .into()
+                        },
+                    );
+                }
+                _ => panic!("Create message while not being on options page"),
+            },
             Message::SelectedOS(os) => {
                 self.page = Page::Options(options::OptionSelection::new(
                     os,
                     config.default_vm_dir.clone().into(),
                 ));
             }
+            // Presets are part of the app's config, so they're saved here
+            Message::Options(options::Message::SavePreset) => {
+                if let Page::Options(ref mut options) = self.page {
+                    if let Some(preset) = options.to_preset() {
+                        let name = preset.name.clone();
+                        let mut presets = config.creation_presets.clone();
+                        // Saving under an existing name updates that preset
+                        let replaced = match presets
+                            .iter_mut()
+                            .find(|existing| existing.name == preset.name)
+                        {
+                            Some(existing) => {
+                                *existing = preset;
+                                true
+                            }
+                            None => {
+                                presets.push(preset);
+                                false
+                            }
+                        };
+                        set_presets(config, config_handler, presets);
+                        options.preset_saved(&name, replaced);
+                    }
+                }
+            }
             Message::Options(msg) => match self.page {
                 Page::Options(ref mut options) => return options.update(msg),
                 _ => panic!("Options message while not being on options page"),
@@ -81,7 +294,9 @@ impl State {
                             return Task::none();
                         }
                     };
-                    let (download_status, task) = download::DownloadStatus::new(instance);
+                    self.creating_release = options.release();
+                    let (download_status, task) =
+                        download::DownloadStatus::new(instance, options.customizations());
                     self.page = Page::Download(download_status);
                     return task;
                 }
@@ -91,20 +306,34 @@ impl State {
                 Page::Download(ref mut download) => return download.update(msg),
                 _ => panic!("Download message while not being on download page"),
             },
+            Message::Docker(msg) => {
+                // Output of a cancelled build may still be arriving
+                if let Page::Docker(ref mut build) = self.page {
+                    return build.update(msg);
+                }
+            }
             Message::FinalizedConfigPath(path) => {
-                self.page = Page::Complete;
-                if let Some(config_handler) = config_handler {
-                    let mut configs = config.existing_vm_configs.clone();
-                    configs.push(path);
-                    if let Err(e) = config.set_existing_vm_configs(config_handler, configs) {
-                        eprintln!("error updating config: {e}");
-                    }
+                self.page = Page::Complete(path.clone());
+                let mut configs = config.existing_vm_configs.clone();
+                configs.push(path.clone());
+                crate::library::set_vm_configs(config, config_handler, configs);
+                if let Some((release, edition)) = self.creating_release.take() {
+                    let mut vm_metadata = config.vm_metadata.clone();
+                    vm_metadata.insert(
+                        path,
+                        VmMetadata {
+                            release: Some(release),
+                            edition,
+                            ..Default::default()
+                        },
+                    );
+                    crate::library::set_vm_metadata(config, config_handler, vm_metadata);
                 }
             }
         }
         Task::none()
     }
-    pub fn view(&self) -> Element<crate::app::Message> {
+    pub fn view(&self, config: &Config) -> Element<crate::app::Message> {
         match self.page {
             Page::Loading => widget::text("Loading")
                 .apply(widget::container)
@@ -114,8 +343,18 @@ impl State {
                 .align_y(Vertical::Center)
                 .into(),
             Page::SelectOS => {
+                let search = widget::search_input("Search operating systems", &self.search)
+                    .id(SEARCH_INPUT_ID.clone())
+                    .on_input(|search| Message::Search(search).into())
+                    .on_clear(Message::Search(String::new()).into())
+                    .on_submit(Message::SelectHighlighted.into());
+
+                let results = self.search_results();
                 let mut list_column = widget::list_column().style(theme::Container::ContextDrawer);
-                for os in &self.os_list {
+                if results.is_empty() {
+                    list_column = list_column.add(widget::text("No operating systems match"));
+                }
+                for (index, os) in results.into_iter().enumerate() {
                     let mut row = widget::row();
 
                     let homepage_button = os.homepage.clone().map(|homepage| {
@@ -125,17 +364,111 @@ impl State {
                     });
                     row = row.push_maybe(homepage_button);
 
-                    let button = widget::button::text(os.pretty_name.clone())
+                    let button = if index == self.highlighted {
+                        widget::button::suggested(os.pretty_name.clone())
+                    } else {
+                        widget::button::text(os.pretty_name.clone())
+                    };
+                    let button = button
                         .on_press(Message::SelectedOS(os.to_owned()).into())
                         .width(Length::Fill);
                     row = row.push(button);
 
                     list_column = list_column.add(row);
                 }
-                widget::scrollable(list_column).into()
+
+                let catalog_status = match (&self.catalog_error, self.refreshing_catalog) {
+                    (_, true) => " • Checking for updates".to_string(),
+                    (Some(e), false) => format!(" • Couldn't update: {e}"),
+                    (None, false) => String::new(),
+                };
+                let catalog_row = widget::row()
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text::caption(format!(
+                            "Catalog from {}{catalog_status}",
+                            self.catalog_date.as_deref().unwrap_or("an unknown date")
+                        ))
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("view-refresh-symbolic"))
+                            .on_press_maybe(
+                                (!self.refreshing_catalog).then(|| Message::RefreshCatalog.into()),
+                            )
+                            .tooltip("Refresh Catalog"),
+                    );
+
+                let custom_image_row = widget::row()
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text::caption("Not in the catalog? Use a local ISO or disk image")
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        widget::button::standard("Custom Image")
+                            .on_press(Message::SelectCustomImage.into()),
+                    );
+
+                let presets = (!config.creation_presets.is_empty()).then(|| {
+                    config.creation_presets.iter().enumerate().fold(
+                        widget::list_column().style(theme::Container::ContextDrawer),
+                        |list, (index, preset)| {
+                            let release = match &preset.edition {
+                                Some(edition) => format!("{} {edition}", preset.release),
+                                None => preset.release.clone(),
+                            };
+                            let issue = self
+                                .preset_issue
+                                .as_ref()
+                                .filter(|(issue_index, _)| *issue_index == index)
+                                .map(|(_, issue)| {
+                                    widget::text::caption(issue.as_str())
+                                        .class(cosmic::style::Text::Accent)
+                                });
+                            let details = widget::column()
+                                .push(widget::text::heading(preset.name.as_str()))
+                                .push(widget::text::caption(format!(
+                                    "{} {release} • {} • {} cores • {} RAM",
+                                    preset.os,
+                                    preset.arch,
+                                    preset.cpu_cores,
+                                    size::Size::from_bytes(preset.ram)
+                                )))
+                                .push_maybe(issue)
+                                .width(Length::Fill);
+                            list.add(
+                                widget::row()
+                                    .align_y(Alignment::Center)
+                                    .push(details)
+                                    .push(
+                                        widget::button::suggested("Use Preset")
+                                            .on_press(Message::ApplyPreset(index).into()),
+                                    )
+                                    .push(
+                                        widget::button::icon(icon::from_name(
+                                            "user-trash-symbolic",
+                                        ))
+                                        .on_press(Message::DeletePreset(index).into())
+                                        .tooltip("Delete Preset"),
+                                    ),
+                            )
+                        },
+                    )
+                });
+
+                widget::column()
+                    .push(catalog_row)
+                    .push_maybe(presets)
+                    .push(custom_image_row)
+                    .push(search)
+                    .push(widget::scrollable(list_column).id(OS_LIST_ID.clone()))
+                    .spacing(theme::active().cosmic().spacing.space_s)
+                    .into()
             }
             Page::Options(ref options) => options.view(),
             Page::Download(ref download) => download.view(),
+            Page::Docker(ref build) => build.view(),
             Page::Finalizing => widget::text("Finalizing VM configuration")
                 .apply(widget::container)
                 .width(Length::Fill)
@@ -143,19 +476,64 @@ impl State {
                 .align_x(Horizontal::Center)
                 .align_y(Vertical::Center)
                 .into(),
-            Page::Complete => widget::text("Complete")
+            Page::Complete(ref config_path) => widget::column()
+                .push(widget::text("Complete"))
+                .push(
+                    widget::button::suggested("Launch VM")
+                        .on_press(crate::library::Message::StartVM(config_path.clone()).into()),
+                )
+                .align_x(Alignment::Center)
+                .spacing(theme::active().cosmic().spacing.space_s)
                 .apply(widget::container)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .align_x(Horizontal::Center)
                 .align_y(Vertical::Center)
                 .into(),
-            Page::Error(ref e) => widget::text(e).into(),
-            _ => todo!(),
+            Page::Error(ref e) => {
+                // Without any catalog, fetching it again is the only way forward
+                let retry = self.catalog_date.is_none().then(|| {
+                    widget::button::standard("Retry").on_press_maybe(
+                        (!self.refreshing_catalog).then(|| Message::RefreshCatalog.into()),
+                    )
+                });
+                widget::column()
+                    .push(widget::text(e))
+                    .push_maybe(retry)
+                    .align_x(Alignment::Center)
+                    .spacing(theme::active().cosmic().spacing.space_s)
+                    .apply(widget::container)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Horizontal::Center)
+                    .align_y(Vertical::Center)
+                    .into()
+            }
+        }
+    }
+}
+
+fn set_presets(
+    config: &mut Config,
+    config_handler: Option<&cosmic_config::Config>,
+    presets: Vec<CreationPreset>,
+) {
+    match config_handler {
+        Some(config_handler) => {
+            if let Err(e) = config.set_creation_presets(config_handler, presets) {
+                eprintln!("error updating config: {e}");
+            }
         }
+        None => config.creation_presets = presets,
     }
 }
 
+fn refresh_catalog() -> Task<crate::app::Message> {
+    Task::perform(catalog::fetch(), |result| {
+        crate::app::Message::Creation(Message::CatalogRefreshed(result)).into()
+    })
+}
+
 #[derive(Debug, Clone)]
 struct SelectableComboBox<T: Display + Clone + PartialEq> {
     state: combo_box::State<T>,
@@ -186,6 +564,9 @@ impl<T: Display + Clone + PartialEq> SelectableComboBox<T> {
     fn selected(&self) -> Option<&T> {
         self.selected.as_ref()
     }
+    fn options(&self) -> &[T] {
+        self.state.options()
+    }
     fn set_values(&mut self, new_entries: impl IntoIterator<Item = T>) {
         let vec: Vec<T> = new_entries.into_iter().collect();
         if self
@@ -224,19 +605,30 @@ pub(super) enum Page {
     SelectOS,
     Options(options::OptionSelection),
     Download(download::DownloadStatus),
-    Docker,
+    Docker(docker::DockerBuild),
     Finalizing,
-    Complete,
+    Complete(PathBuf),
     Error(String),
 }
 
 #[derive(Clone, Debug)]
 pub(super) enum Message {
-    OSList(Vec<OS>),
+    CachedCatalog(Option<Catalog>),
+    RefreshCatalog,
+    CatalogRefreshed(Result<Catalog, String>),
     SelectedOS(OS),
+    ApplyPreset(usize),
+    DeletePreset(usize),
+    SelectCustomImage,
+    SelectedCustomImage(PathBuf),
+    CreateFromImage(String),
+    Search(String),
+    MoveHighlight(isize),
+    SelectHighlighted,
     Options(options::Message),
     StartDownloads(String),
     Download(download::Message),
+    Docker(docker::Message),
     Error(String),
     ChangePage(Box<Page>),
     FinalizedConfigPath(PathBuf),
@@ -0,0 +1,468 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use cosmic::{
+    app::command::Task,
+    iced::{futures::channel::mpsc::Sender, task, Alignment, Length},
+    theme, widget, Apply, Element,
+};
+use futures_util::SinkExt;
+use quickget_core::{QGDockerSource, QuickgetInstance};
+use tokio::{io::AsyncReadExt, process::Command};
+
+use super::options::Customizations;
+use crate::serial::Terminal;
+
+/// Overrides the detected container runtime, e.g. with a stub for testing.
+const RUNTIME_OVERRIDE_VAR: &str = "QUICKEMU_SPACE_CONTAINER_RUNTIME";
+/// Build output is often long, so only the tail is displayed.
+const DISPLAYED_LINES: usize = 500;
+
+/// Builds installation media within a container, which some quickget OSes need after their
+/// downloads finish.
+#[derive(Debug, Clone)]
+pub struct DockerBuild {
+    instance: QuickgetInstance,
+    customizations: Customizations,
+    sources: Vec<QGDockerSource>,
+    runtime: Option<PathBuf>,
+    /// Names the images and containers of the current build, so that they can be removed.
+    build_id: String,
+    log: Terminal,
+    status: Status,
+    handle: Option<task::Handle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Building { step: usize },
+    Failed(String),
+    Done,
+}
+
+impl DockerBuild {
+    pub(super) fn new(
+        instance: QuickgetInstance,
+        customizations: Customizations,
+        sources: Vec<QGDockerSource>,
+    ) -> (Self, Task<crate::app::Message>) {
+        let mut build = Self {
+            instance,
+            customizations,
+            sources,
+            runtime: detect_runtime(),
+            build_id: String::new(),
+            log: Terminal::default(),
+            status: Status::Building { step: 0 },
+            handle: None,
+        };
+        let task = build.start();
+        (build, task)
+    }
+
+    fn start(&mut self) -> Task<crate::app::Message> {
+        self.log.clear();
+        let Some(runtime) = self.runtime.clone() else {
+            self.status = Status::Failed(
+                "Neither Docker nor Podman could be found. Install one of them to build this OS."
+                    .to_string(),
+            );
+            return Task::none();
+        };
+        self.status = Status::Building { step: 0 };
+        // Unique, since other builds may be running at the same time
+        self.build_id = format!(
+            "quickemu-space-build-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_millis()
+        );
+
+        let (task, handle) = Task::abortable(Task::run(
+            run_build(runtime, self.build_id.clone(), self.sources.clone()),
+            |event| crate::app::Message::from(Message::Event(event)).into(),
+        ));
+        self.handle = Some(handle);
+        task
+    }
+
+    /// Stops the build. Killing the runtime's client doesn't stop its container, so that's
+    /// removed along with the images built so far.
+    fn abort(&mut self) -> Task<crate::app::Message> {
+        let Some(handle) = self.handle.take() else {
+            return Task::none();
+        };
+        handle.abort();
+        let Some(runtime) = self.runtime.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            clean_up(runtime, self.build_id.clone(), self.sources.len()),
+            |()| crate::app::Message::None.into(),
+        )
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Event(Event::Step(step)) => self.status = Status::Building { step },
+            Message::Event(Event::Output(bytes)) => self.log.feed(&bytes),
+            Message::Event(Event::Failed(e)) => self.status = Status::Failed(e),
+            Message::Event(Event::Done) => {
+                self.status = Status::Done;
+                return super::download::finalize(
+                    self.instance.clone(),
+                    self.customizations.clone(),
+                );
+            }
+            Message::Retry => {
+                let abort = self.abort();
+                self.runtime = detect_runtime();
+                return abort.chain(self.start());
+            }
+            Message::Cancel => {
+                let abort = self.abort();
+                return abort.chain(Task::perform(
+                    async move {
+                        crate::app::Message::from(super::Message::ChangePage(
+                            super::Page::SelectOS.into(),
+                        ))
+                    },
+                    |msg| msg.into(),
+                ));
+            }
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4("Building Installation Media"));
+        if let Some(runtime) = &self.runtime {
+            list = list.add(widget::text::caption(format!(
+                "Using {}",
+                runtime.display()
+            )));
+        }
+
+        let status = match &self.status {
+            Status::Building { step } => widget::text(format!(
+                "Running build step {} of {}",
+                step + 1,
+                self.sources.len() * 2
+            )),
+            Status::Failed(e) => {
+                widget::text(format!("Build failed: {e}")).class(cosmic::style::Text::Accent)
+            }
+            Status::Done => widget::text("Build complete"),
+        };
+        list = list.add(status);
+
+        let output = if self.log.is_empty() {
+            widget::text("No output yet")
+        } else {
+            widget::text(self.log.tail(DISPLAYED_LINES))
+        }
+        .font(cosmic::font::mono())
+        .width(Length::Fill);
+        list = list.add(
+            widget::list_column()
+                .style(theme::Container::ContextDrawer)
+                .add(widget::scrollable(output).anchor_bottom())
+                .apply(widget::container)
+                .height(Length::Fill),
+        );
+
+        let nav_row = {
+            let cancel = widget::button::suggested("Cancel").on_press(Message::Cancel.into());
+            let retry = widget::button::suggested("Retry").on_press_maybe(
+                matches!(self.status, Status::Failed(_)).then(|| Message::Retry.into()),
+            );
+
+            widget::row().align_y(Alignment::Center).push(cancel).push(
+                widget::container(retry)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+/// Finds a container runtime, preferring Docker over Podman. Setting
+/// `QUICKEMU_SPACE_CONTAINER_RUNTIME` to a binary path skips the search.
+fn detect_runtime() -> Option<PathBuf> {
+    if let Some(runtime) = std::env::var_os(RUNTIME_OVERRIDE_VAR) {
+        return Some(PathBuf::from(runtime));
+    }
+    let path = std::env::var_os("PATH")?;
+    ["docker", "podman"].into_iter().find_map(|name| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(name))
+            .find(|binary| binary.is_file())
+    })
+}
+
+/// Builds the image of each source, then runs it to produce the installation media. Both the
+/// build and the run count as a step. Images and containers are named after the build ID and
+/// the source's index, and are removed once the build ends.
+fn run_build(
+    runtime: PathBuf,
+    build_id: String,
+    sources: Vec<QGDockerSource>,
+) -> impl futures_util::Stream<Item = Event> {
+    cosmic::iced::stream::channel(64, move |mut output| async move {
+        let event = build_sources(&runtime, &build_id, &sources, &mut output).await;
+        clean_up(runtime, build_id, sources.len()).await;
+        _ = output.send(event).await;
+    })
+}
+
+/// Runs the build steps, returning the event which ends the build.
+async fn build_sources(
+    runtime: &Path,
+    build_id: &str,
+    sources: &[QGDockerSource],
+    output: &mut Sender<Event>,
+) -> Event {
+    for (index, source) in sources.iter().enumerate() {
+        let name = format!("{build_id}-{index}");
+
+        let mut build = Command::new(runtime);
+        build.arg("build").arg("--tag").arg(&name).arg(&source.url);
+
+        let mut run = Command::new(runtime);
+        run.arg("run").arg("--rm").arg("--name").arg(&name);
+        if source.privileged {
+            run.arg("--privileged");
+        }
+        for dir in &source.shared_dirs {
+            run.arg("--volume")
+                .arg(format!("{}:{}", dir.display(), dir.display()));
+        }
+        run.arg(&name);
+
+        for (offset, command) in [build, run].iter_mut().enumerate() {
+            _ = output.send(Event::Step(index * 2 + offset)).await;
+            if let Err(e) = run_command(command, output).await {
+                return Event::Failed(e);
+            }
+        }
+
+        if !source.output_filename.is_file() {
+            let e = format!("{} wasn't produced", source.output_filename.display());
+            return Event::Failed(e);
+        }
+    }
+    Event::Done
+}
+
+/// Removes the containers and images of a build. Failures are ignored, since not all of them
+/// may exist.
+async fn clean_up(runtime: PathBuf, build_id: String, sources: usize) {
+    let names: Vec<_> = (0..sources)
+        .map(|index| format!("{build_id}-{index}"))
+        .collect();
+    for subcommand in ["rm", "rmi"] {
+        _ = Command::new(&runtime)
+            .arg(subcommand)
+            .arg("--force")
+            .args(&names)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+/// Runs a command to completion, forwarding everything it writes to stdout and stderr.
+async fn run_command(command: &mut Command, output: &mut Sender<Event>) -> Result<(), String> {
+    let program = Path::new(command.as_std().get_program()).to_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Couldn't run {}: {e}", program.display()))?;
+    let mut stdout = child.stdout.take().expect("stdout should be piped");
+    let mut stderr = child.stderr.take().expect("stderr should be piped");
+
+    let (mut stdout_buf, mut stderr_buf) = (vec![0; 4096], vec![0; 4096]);
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    while stdout_open || stderr_open {
+        let bytes = tokio::select! {
+            read = stdout.read(&mut stdout_buf), if stdout_open => match read {
+                Ok(0) | Err(_) => {
+                    stdout_open = false;
+                    continue;
+                }
+                Ok(len) => stdout_buf[..len].to_vec(),
+            },
+            read = stderr.read(&mut stderr_buf), if stderr_open => match read {
+                Ok(0) | Err(_) => {
+                    stderr_open = false;
+                    continue;
+                }
+                Ok(len) => stderr_buf[..len].to_vec(),
+            },
+        };
+        _ = output.send(Event::Output(bytes)).await;
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Couldn't wait for {}: {e}", program.display()))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {status}", program.display()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    Step(usize),
+    Output(Vec<u8>),
+    Failed(String),
+    Done,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Message {
+    Event(Event),
+    Retry,
+    Cancel,
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Creation(super::Message::Docker(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    /// Writes a script standing in for the container runtime, which logs its arguments.
+    fn stub_runtime(dir: &Path, run_exit_code: u8) -> PathBuf {
+        let runtime = dir.join("runtime");
+        std::fs::write(
+            &runtime,
+            format!(
+                "#!/bin/sh\n\
+                 echo \"$@\" >> {log}\n\
+                 case \"$1\" in\n\
+                 build) echo building ;;\n\
+                 run) echo running; echo warning >&2; exit {run_exit_code} ;;\n\
+                 esac\n",
+                log = dir.join("calls").display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runtime, std::fs::Permissions::from_mode(0o755)).unwrap();
+        runtime
+    }
+
+    fn source(output_filename: PathBuf) -> QGDockerSource {
+        QGDockerSource {
+            url: "https://example.com/builder.git".to_string(),
+            privileged: true,
+            shared_dirs: vec![],
+            output_filename,
+        }
+    }
+
+    fn output(events: &[Event]) -> String {
+        let mut terminal = Terminal::default();
+        for event in events {
+            if let Event::Output(bytes) = event {
+                terminal.feed(bytes);
+            }
+        }
+        terminal.text()
+    }
+
+    #[tokio::test]
+    async fn successful_builds_report_each_step() {
+        let dir = temp_dir("docker-success");
+        let runtime = stub_runtime(&dir, 0);
+        let media = dir.join("media.iso");
+        std::fs::write(&media, b"iso").unwrap();
+
+        let events: Vec<Event> = run_build(runtime, "build".to_string(), vec![source(media)])
+            .collect()
+            .await;
+
+        let steps: Vec<usize> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Step(step) => Some(*step),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(steps, [0, 1]);
+        let output = output(&events);
+        assert!(output.contains("building"));
+        assert!(output.contains("running"));
+        assert!(output.contains("warning"));
+        assert!(matches!(events.last(), Some(Event::Done)));
+
+        let calls = std::fs::read_to_string(dir.join("calls")).unwrap();
+        assert!(calls.contains("run --rm --name build-0 --privileged build-0"));
+        assert!(calls.contains("rmi --force build-0"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failing_steps_stop_the_build() {
+        let dir = temp_dir("docker-failure");
+        let runtime = stub_runtime(&dir, 3);
+
+        let events: Vec<Event> = run_build(
+            runtime,
+            "build".to_string(),
+            vec![source(dir.join("media.iso"))],
+        )
+        .collect()
+        .await;
+
+        assert!(output(&events).contains("running"));
+        match events.last() {
+            Some(Event::Failed(e)) => assert!(e.contains("exit status: 3"), "{e}"),
+            event => panic!("expected the build to fail, got {event:?}"),
+        }
+        assert!(!events.iter().any(|event| matches!(event, Event::Done)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_output_fails_the_build() {
+        let dir = temp_dir("docker-missing");
+        let runtime = stub_runtime(&dir, 0);
+
+        let events: Vec<Event> = run_build(
+            runtime,
+            "build".to_string(),
+            vec![source(dir.join("media.iso"))],
+        )
+        .collect()
+        .await;
+
+        assert!(matches!(events.last(), Some(Event::Failed(e)) if e.contains("wasn't produced")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,20 +6,29 @@ use cosmic::{
     widget, Element,
 };
 use futures_util::StreamExt;
-use quickget_core::{QGDownload, QuickgetInstance};
+use quickget_core::{QGDockerSource, QGDownload, QuickgetInstance};
 use size::Size;
 
+use super::{docker::DockerBuild, options::Customizations};
+
 #[derive(Debug, Clone)]
 pub struct DownloadStatus {
     instance: QuickgetInstance,
+    customizations: Customizations,
     downloads: Vec<Download>,
+    /// Container builds which some OSes need once their downloads are done.
+    docker_sources: Vec<QGDockerSource>,
     handle: task::Handle,
 }
 
 impl DownloadStatus {
-    pub(super) fn new(mut instance: QuickgetInstance) -> (Self, Task<crate::app::Message>) {
+    pub(super) fn new(
+        mut instance: QuickgetInstance,
+        customizations: Customizations,
+    ) -> (Self, Task<crate::app::Message>) {
         let client = reqwest::Client::new();
         let downloads = instance.get_downloads();
+        let docker_sources = instance.get_docker_sources();
         let (downloads, tasks): (Vec<_>, Vec<_>) = downloads
             .into_iter()
             .enumerate()
@@ -31,7 +40,9 @@ impl DownloadStatus {
         (
             Self {
                 instance,
+                customizations,
                 downloads,
+                docker_sources,
                 handle,
             },
             task,
@@ -51,34 +62,24 @@ impl DownloadStatus {
                     |msg| msg.into(),
                 );
             }
+            Message::Finalize if self.docker_sources.is_empty() => {
+                return finalize(self.instance.clone(), self.customizations.clone());
+            }
             Message::Finalize => {
-                let finalize_page = Task::perform(
+                let (build, task) = DockerBuild::new(
+                    self.instance.clone(),
+                    self.customizations.clone(),
+                    self.docker_sources.clone(),
+                );
+                let build_page = Task::perform(
                     async move {
                         crate::app::Message::from(super::Message::ChangePage(
-                            super::Page::Finalizing.into(),
+                            super::Page::Docker(build).into(),
                         ))
                     },
                     |msg| msg.into(),
                 );
-
-                let instance = self.instance.clone();
-                let finalize = Task::perform(
-                    async move {
-                        let config_file_path = instance.get_config_file_path().to_owned();
-
-                        let finalize_result =
-                            tokio::task::spawn_blocking(move || instance.create_config())
-                                .await
-                                .expect("Couldn't spawn thread");
-                        crate::app::Message::from(match finalize_result {
-                            Ok(_) => super::Message::FinalizedConfigPath(config_file_path),
-                            Err(e) => super::Message::Error(format!("Error creating config: {e}")),
-                        })
-                    },
-                    |msg| msg.into(),
-                );
-
-                return finalize_page.chain(finalize);
+                return build_page.chain(task);
             }
             Message::Specific(SpecificDownloadMessage { id, msg }) => {
                 let download = self
@@ -138,6 +139,46 @@ impl DownloadStatus {
     }
 }
 
+/// Creates the config once everything it needs is in place, applying the settings quickget doesn't
+/// handle.
+pub(super) fn finalize(
+    instance: QuickgetInstance,
+    customizations: Customizations,
+) -> Task<crate::app::Message> {
+    let finalize_page = Task::perform(
+        async move {
+            crate::app::Message::from(super::Message::ChangePage(super::Page::Finalizing.into()))
+        },
+        |msg| msg.into(),
+    );
+
+    let finalize = Task::perform(
+        async move {
+            let config_file_path = instance.get_config_file_path().to_owned();
+
+            let config_path = config_file_path.clone();
+            let finalize_result = tokio::task::spawn_blocking(move || {
+                instance.create_config().map_err(|e| e.to_string())?;
+                if !customizations.is_empty() {
+                    let mut vm = crate::vm::Vm::load(config_path)?;
+                    customizations.apply(&mut vm);
+                    vm.save()?;
+                }
+                Ok::<_, String>(())
+            })
+            .await
+            .expect("Couldn't spawn thread");
+            crate::app::Message::from(match finalize_result {
+                Ok(_) => super::Message::FinalizedConfigPath(config_file_path),
+                Err(e) => super::Message::Error(format!("Error creating config: {e}")),
+            })
+        },
+        |msg| msg.into(),
+    );
+
+    finalize_page.chain(finalize)
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum Message {
     CancelDownloads,
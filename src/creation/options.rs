@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{path::PathBuf, sync::LazyLock};
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
 
 use ashpd::desktop::file_chooser::SelectedFiles;
 use cosmic::{
@@ -14,18 +18,27 @@ use quickemu_core::data::{AArch64Machine, Arch, Riscv64Machine, X86_64Machine};
 use quickget_core::{data_structures::OS, QuickgetConfig, QuickgetInstance};
 
 use super::{Page, SelectableComboBox};
+use crate::{
+    config::{CreationPreset, LATEST_RELEASE},
+    vm::{Preallocation, Vm},
+};
 
 static TOTAL_CPU_CORES: LazyLock<f64> =
     LazyLock::new(|| QuickgetInstance::get_total_cpu_cores() as f64);
-static RECOMMENDED_CPU_CORES: LazyLock<usize> =
+pub(crate) static RECOMMENDED_CPU_CORES: LazyLock<usize> =
     LazyLock::new(QuickgetInstance::get_recommended_cpu_cores);
 static TOTAL_RAM: LazyLock<f64> = LazyLock::new(|| QuickgetInstance::get_total_ram() as f64);
-static RECOMMENDED_RAM: LazyLock<f64> =
+pub(crate) static RECOMMENDED_RAM: LazyLock<f64> =
     LazyLock::new(|| QuickgetInstance::get_recommended_ram() as f64);
+const MAX_DISK_SIZE: f64 = 2048.0 * size::consts::GiB as f64;
+static SWTPM_INSTALLED: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("swtpm").is_file()))
+});
 
 #[derive(Debug, Clone)]
 pub(crate) struct OptionSelection {
-    selected_os: OS,
+    source: Source,
     release_list: SelectableComboBox<String>,
     edition_list: SelectableComboBox<String>,
     arch_list: SelectableComboBox<Arch>,
@@ -34,18 +47,214 @@ pub(crate) struct OptionSelection {
     vm_name: Option<String>,
     default_vm_name: Option<String>,
     directory: PathBuf,
+    /// Why the VM directory couldn't be created, after that was attempted.
+    directory_error: Option<String>,
+    /// Problems with the directory and name, checked whenever the options change so that the
+    /// view doesn't touch the filesystem.
+    directory_issue: Option<String>,
+    name_issue: Option<String>,
+    /// A free name similar to the one entered, when that's taken.
+    name_suggestion: Option<String>,
+    free_space: Option<u64>,
+    shared_dir: Option<PathBuf>,
+    show_advanced: bool,
+    /// Disk and firmware settings, which are left to quickget until they're changed.
+    disk_size: Option<f64>,
+    preallocation: Option<Preallocation>,
+    uefi: Option<bool>,
+    secure_boot: Option<bool>,
+    tpm: Option<bool>,
+    preset_name: String,
+    /// Whether a saved preset follows new releases rather than keeping the selected one.
+    preset_latest_release: bool,
+    /// Confirms that the preset was saved, until it's changed again.
+    preset_status: Option<String>,
+}
+
+/// What a VM is created from.
+#[derive(Debug, Clone)]
+enum Source {
+    /// An OS from the quickget catalog, which is downloaded before the config is created.
+    Catalog(OS),
+    /// A local ISO or disk image, which is used as is.
+    CustomImage(PathBuf),
+}
+
+/// Settings chosen in the wizard which quickget doesn't handle itself. They're applied to the
+/// config once quickget has created it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Customizations {
+    shared_dir: Option<PathBuf>,
+    disk_size: Option<u64>,
+    preallocation: Option<Preallocation>,
+    uefi: Option<bool>,
+    secure_boot: Option<bool>,
+    tpm: Option<bool>,
+}
+
+impl Customizations {
+    pub(super) fn is_empty(&self) -> bool {
+        self.shared_dir.is_none()
+            && self.disk_size.is_none()
+            && self.preallocation.is_none()
+            && self.uefi.is_none()
+            && self.secure_boot.is_none()
+            && self.tpm.is_none()
+    }
+
+    pub(super) fn apply(&self, vm: &mut Vm) {
+        if let Some(shared_dir) = &self.shared_dir {
+            vm.set_shared_dir(Some(shared_dir.clone()));
+        }
+        if let Some(disk_size) = self.disk_size {
+            vm.set_disk_size(disk_size);
+        }
+        if let Some(preallocation) = self.preallocation {
+            vm.set_preallocation(preallocation);
+        }
+        if self.uefi.is_some() || self.secure_boot.is_some() {
+            // Secure Boot requires UEFI, so turning it on switches OSes which default to BIOS
+            let uefi = self
+                .uefi
+                .unwrap_or_else(|| self.secure_boot == Some(true) || vm.uefi());
+            let secure_boot = uefi && self.secure_boot.unwrap_or_else(|| vm.secure_boot());
+            vm.set_firmware(uefi, secure_boot);
+        }
+        if let Some(tpm) = self.tpm {
+            vm.set_tpm(tpm);
+        }
+    }
 }
 
 impl OptionSelection {
     pub(super) fn new(selected_os: OS, default_vm_dir: PathBuf) -> Self {
+        Self::with_source(Source::Catalog(selected_os), default_vm_dir)
+    }
+
+    pub(super) fn new_custom(image: PathBuf, default_vm_dir: PathBuf) -> Self {
+        Self::with_source(Source::CustomImage(image), default_vm_dir)
+    }
+
+    /// Restores the options saved in a preset, failing if its release, edition or architecture is
+    /// no longer in the catalog.
+    pub(super) fn from_preset(selected_os: OS, preset: &CreationPreset) -> Result<Self, String> {
+        let mut options = Self::new(selected_os, preset.directory.clone());
+        let unavailable = |what: &str| {
+            format!(
+                "The {what} of the \"{}\" preset is no longer available for {}",
+                preset.name, preset.os
+            )
+        };
+
+        let arch = options
+            .arch_list
+            .options()
+            .iter()
+            .find(|arch| arch.to_string() == preset.arch)
+            .copied()
+            .ok_or_else(|| unavailable("architecture"))?;
+        options.select_arch(arch);
+        if let Some(edition) = &preset.edition {
+            if !options.edition_list.options().contains(edition) {
+                return Err(unavailable("edition"));
+            }
+            options.select_edition(edition.clone());
+        }
+        let release = if preset.release == LATEST_RELEASE {
+            latest_release(options.release_list.options())
+        } else {
+            options
+                .release_list
+                .options()
+                .iter()
+                .find(|release| **release == preset.release)
+        }
+        .cloned()
+        .ok_or_else(|| unavailable("release"))?;
+        options.select_release(release);
+
+        options.cpu_cores = preset.cpu_cores.clamp(1, *TOTAL_CPU_CORES as usize);
+        options.ram = (preset.ram as f64).min(*TOTAL_RAM);
+        options.shared_dir = preset.shared_dir.clone();
+        options.disk_size = preset.disk_size.map(|size| size as f64);
+        options.preallocation = preset.preallocation;
+        options.uefi = preset.uefi;
+        options.secure_boot = preset.secure_boot;
+        options.tpm = preset.tpm;
+        options.preset_name = preset.name.clone();
+        options.preset_latest_release = preset.release == LATEST_RELEASE;
+        options.check_name();
+        Ok(options)
+    }
+
+    /// The selected release and edition, which aren't known for custom images.
+    pub(super) fn release(&self) -> Option<(String, Option<String>)> {
+        match self.source {
+            Source::Catalog(_) => Some((
+                self.release_list.selected()?.clone(),
+                self.edition_list.selected().cloned(),
+            )),
+            Source::CustomImage(_) => None,
+        }
+    }
+
+    /// The current options as a preset. Custom images can't be saved, since they're meant to be
+    /// used once.
+    pub(super) fn to_preset(&self) -> Option<CreationPreset> {
+        let Source::Catalog(selected_os) = &self.source else {
+            return None;
+        };
+        let name = self.preset_name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let release = if self.preset_latest_release {
+            LATEST_RELEASE.to_string()
+        } else {
+            self.release_list.selected()?.clone()
+        };
+        Some(CreationPreset {
+            name: name.to_string(),
+            os: selected_os.name.clone(),
+            release,
+            edition: self.edition_list.selected().cloned(),
+            arch: self.arch_list.selected()?.to_string(),
+            cpu_cores: self.cpu_cores,
+            ram: self.ram as u64,
+            directory: self.directory.clone(),
+            shared_dir: self.shared_dir.clone(),
+            disk_size: self.disk_size.map(|size| size as u64),
+            preallocation: self.preallocation,
+            uefi: self.uefi,
+            secure_boot: self.secure_boot,
+            tpm: self.tpm,
+        })
+    }
+
+    fn with_source(source: Source, default_vm_dir: PathBuf) -> Self {
         let mut options = Self {
-            selected_os,
+            source,
             release_list: SelectableComboBox::new_empty(),
             edition_list: SelectableComboBox::new_empty(),
             arch_list: SelectableComboBox::new_empty(),
             cpu_cores: *RECOMMENDED_CPU_CORES,
             ram: *RECOMMENDED_RAM,
             directory: default_vm_dir,
+            directory_error: None,
+            directory_issue: None,
+            name_issue: None,
+            name_suggestion: None,
+            free_space: None,
+            shared_dir: None,
+            show_advanced: false,
+            disk_size: None,
+            preallocation: None,
+            uefi: None,
+            secure_boot: None,
+            tpm: None,
+            preset_name: String::new(),
+            preset_latest_release: false,
+            preset_status: None,
             vm_name: None,
             default_vm_name: None,
         };
@@ -65,15 +274,19 @@ impl OptionSelection {
             },
         };
         options.arch_list.try_select(preferred_arch);
+        options.set_default_vm_name();
+        options.check_name();
 
         options
     }
 
     pub(super) fn to_instance(&self, vm_name: &str) -> Result<QuickgetInstance, String> {
+        let Source::Catalog(selected_os) = &self.source else {
+            return Err("Custom images aren't created through quickget".to_string());
+        };
         let qg_config = QuickgetConfig {
-            os: self.selected_os.name.clone(),
-            config: self
-                .selected_os
+            os: selected_os.name.clone(),
+            config: selected_os
                 .releases
                 .iter()
                 .filter(|config| self.release_list.selected().unwrap() == &config.release)
@@ -86,6 +299,98 @@ impl OptionSelection {
             .map_err(|e| e.to_string())
     }
 
+    /// Creates a VM from the selected custom image, returning its config path.
+    pub(super) fn create_from_image(&self, vm_name: &str) -> Result<PathBuf, String> {
+        let Source::CustomImage(image) = &self.source else {
+            return Err("Catalog OSes are created through quickget".to_string());
+        };
+        let arch = *self
+            .arch_list
+            .selected()
+            .ok_or("No architecture has been selected")?;
+        let customizations = self.customizations();
+        let vm = Vm::create_from_image(
+            &self.directory,
+            vm_name,
+            image,
+            arch,
+            self.cpu_cores,
+            self.ram as u64,
+            |vm| customizations.apply(vm),
+        )?;
+        Ok(vm.config_path().to_owned())
+    }
+
+    fn os_name(&self) -> Option<&str> {
+        match &self.source {
+            Source::Catalog(os) => Some(&os.name),
+            Source::CustomImage(_) => None,
+        }
+    }
+
+    pub(super) fn customizations(&self) -> Customizations {
+        Customizations {
+            shared_dir: self.shared_dir.clone(),
+            disk_size: self
+                .disk_size()
+                .filter(|_| self.creates_disk())
+                .map(|size| size as u64),
+            preallocation: self.preallocation.filter(|_| self.creates_disk()),
+            uefi: self.uefi.filter(|_| self.bios_supported()),
+            secure_boot: self.secure_boot.filter(|_| self.secure_boot_supported()),
+            tpm: self.tpm.filter(|_| self.tpm_supported()),
+        }
+    }
+
+    /// Disk images chosen as custom images are used as they are, while everything else gets a
+    /// new disk.
+    fn creates_disk(&self) -> bool {
+        match &self.source {
+            Source::Catalog(_) => true,
+            Source::CustomImage(image) => image
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("iso")),
+        }
+    }
+
+    /// The smallest disk the OS can be installed to, leaving some room for updates.
+    fn min_disk_size(&self) -> f64 {
+        let gib = match self.os_name() {
+            // Windows 11's installer refuses smaller disks
+            Some(name) if name.starts_with("windows") => 64,
+            Some("macos") => 64,
+            _ => 8,
+        };
+        (gib * size::consts::GiB) as f64
+    }
+
+    /// The chosen disk size, or nothing while quickemu's default is used.
+    fn disk_size(&self) -> Option<f64> {
+        self.disk_size.map(|size| size.max(self.min_disk_size()))
+    }
+
+    fn is_x86_64(&self) -> bool {
+        matches!(self.arch_list.selected(), Some(Arch::X86_64 { .. }))
+    }
+
+    fn is_macos(&self) -> bool {
+        self.os_name() == Some("macos")
+    }
+
+    /// Legacy BIOS firmware is only available for x86_64 guests, and macOS requires UEFI.
+    fn bios_supported(&self) -> bool {
+        self.is_x86_64() && !self.is_macos()
+    }
+
+    /// QEMU only ships Secure Boot capable UEFI firmware for x86_64.
+    fn secure_boot_supported(&self) -> bool {
+        self.is_x86_64() && !self.is_macos() && self.uefi != Some(false)
+    }
+
+    fn tpm_supported(&self) -> bool {
+        !matches!(self.arch_list.selected(), Some(Arch::Riscv64 { .. })) && *SWTPM_INSTALLED
+    }
+
     pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
         match msg {
             Message::SelectedRelease(release) => self.select_release(release),
@@ -93,6 +398,12 @@ impl OptionSelection {
             Message::SelectedArch(arch) => self.select_arch(arch),
             Message::SetRAM(ram) => self.ram = ram,
             Message::SetCPUCores(cores) => self.cpu_cores = cores,
+            Message::SetDiskSize(size) => {
+                let gib = size::consts::GiB as f64;
+                self.disk_size = Some((size / gib).round() * gib);
+            }
+            Message::ResetDiskSize => self.disk_size = None,
+            Message::SetPreallocation(preallocation) => self.preallocation = Some(preallocation),
             Message::SelectVMDir => {
                 return Task::perform(crate::app::select_dir(), |dir| {
                     match dir {
@@ -102,7 +413,55 @@ impl OptionSelection {
                     .into()
                 })
             }
-            Message::SelectedVMDir(dir) => self.directory = dir,
+            Message::SelectedVMDir(dir) => {
+                self.directory = dir;
+                self.directory_error = None;
+                self.set_default_vm_name();
+            }
+            Message::CreateVMDir => {
+                let directory = self.directory.clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            std::fs::create_dir_all(&directory).map_err(|e| {
+                                format!("Couldn't create {}: {e}", directory.display())
+                            })
+                        })
+                        .await
+                        .expect("Couldn't spawn thread")
+                    },
+                    |result| crate::app::Message::from(Message::CreatedVMDir(result)).into(),
+                );
+            }
+            Message::CreatedVMDir(result) => {
+                self.directory_error = result.err();
+                self.set_default_vm_name();
+            }
+            Message::SelectSharedDir => {
+                return Task::perform(crate::app::select_dir(), |dir| {
+                    match dir {
+                        Some(dir) => crate::app::Message::from(Message::SelectedSharedDir(dir)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedSharedDir(dir) => self.shared_dir = Some(dir),
+            Message::ClearSharedDir => self.shared_dir = None,
+            Message::ToggleAdvanced => self.show_advanced = !self.show_advanced,
+            Message::SetUefi(uefi) => self.uefi = uefi,
+            Message::SetSecureBoot(secure_boot) => self.secure_boot = secure_boot,
+            Message::SetTpm(tpm) => self.tpm = tpm,
+            Message::SetPresetName(name) => {
+                self.preset_name = name;
+                self.preset_status = None;
+            }
+            Message::SetPresetLatestRelease(latest) => {
+                self.preset_latest_release = latest;
+                self.preset_status = None;
+            }
+            // Handled by the parent, which owns the presets
+            Message::SavePreset => {}
             Message::SelectedVMName(name) => self.vm_name = Some(name),
             Message::FinalizeVMName => {
                 if self
@@ -114,12 +473,15 @@ impl OptionSelection {
                 }
             }
         }
+        self.check_name();
         Task::none()
     }
 
     fn refresh_releases(&mut self) {
-        let releases = self
-            .selected_os
+        let Source::Catalog(selected_os) = &self.source else {
+            return;
+        };
+        let releases = selected_os
             .releases
             .iter()
             .filter(|config| {
@@ -138,8 +500,10 @@ impl OptionSelection {
     }
 
     fn refresh_editions(&mut self) {
-        let editions = self
-            .selected_os
+        let Source::Catalog(selected_os) = &self.source else {
+            return;
+        };
+        let editions = selected_os
             .releases
             .iter()
             .filter(|config| {
@@ -159,8 +523,22 @@ impl OptionSelection {
     }
 
     fn refresh_architectures(&mut self) {
-        let architectures = self
-            .selected_os
+        let Source::Catalog(selected_os) = &self.source else {
+            // A custom image could be built for any architecture QEMU emulates
+            self.arch_list.set_values([
+                Arch::X86_64 {
+                    machine: X86_64Machine::Standard,
+                },
+                Arch::AArch64 {
+                    machine: AArch64Machine::Standard,
+                },
+                Arch::Riscv64 {
+                    machine: Riscv64Machine::Standard,
+                },
+            ]);
+            return;
+        };
+        let architectures = selected_os
             .releases
             .iter()
             .filter(|config| {
@@ -200,6 +578,16 @@ impl OptionSelection {
     }
 
     fn set_default_vm_name(&mut self) {
+        let selected_os = match &self.source {
+            Source::Catalog(selected_os) => selected_os,
+            Source::CustomImage(image) => {
+                let stem = image.file_stem().unwrap_or_default().to_string_lossy();
+                self.default_vm_name = self.arch_list.selected().map(|arch| {
+                    crate::vm::unique_name(&self.directory, &default_vm_name(&stem, *arch))
+                });
+                return;
+            }
+        };
         self.default_vm_name = match (
             self.release_list.selected(),
             self.edition_list.selected().map(|e| e.as_str()),
@@ -209,16 +597,64 @@ impl OptionSelection {
             (Some(release), edition, no_editions, Some(arch))
                 if edition.is_some() || no_editions =>
             {
-                Some(default_vm_name(&self.selected_os, release, edition, *arch))
+                let mut prefix = format!("{}-{}", selected_os.name, release);
+                if let Some(edition) = edition {
+                    prefix.push('-');
+                    prefix.push_str(edition)
+                }
+                Some(crate::vm::unique_name(
+                    &self.directory,
+                    &default_vm_name(&prefix, *arch),
+                ))
             }
             _ => None,
         };
     }
 
+    /// What still has to be chosen before the VM's name can be suggested.
+    fn selection_issue(&self) -> Option<&'static str> {
+        if self.default_vm_name.is_some() {
+            None
+        } else if self.release_list.selected().is_none() && !self.release_list.is_empty() {
+            Some("Select a release")
+        } else if self.edition_list.selected().is_none() && !self.edition_list.is_empty() {
+            Some("Select an edition")
+        } else {
+            Some("Select an architecture")
+        }
+    }
+
+    fn vm_name(&self) -> Option<&str> {
+        self.vm_name.as_deref().or(self.default_vm_name.as_deref())
+    }
+
+    /// Checks the directory and name against the filesystem.
+    fn check_name(&mut self) {
+        self.directory_issue = if !self.directory.exists() {
+            Some(format!("{} doesn't exist", self.directory.display()))
+        } else if !self.directory.is_dir() {
+            Some(format!("{} isn't a directory", self.directory.display()))
+        } else {
+            None
+        };
+        // A missing directory is explained next to the directory instead
+        self.name_issue = self
+            .vm_name()
+            .filter(|_| self.directory_issue.is_none())
+            .and_then(|vm_name| crate::vm::name_issue(&self.directory, vm_name));
+        self.name_suggestion = self
+            .vm_name()
+            .filter(|_| self.name_issue.is_some())
+            .filter(|vm_name| !vm_name.is_empty() && !vm_name.contains('/'))
+            .map(|vm_name| crate::vm::unique_name(&self.directory, vm_name))
+            .filter(|suggestion| Some(suggestion.as_str()) != self.vm_name());
+        self.free_space = crate::storage::free_space(&self.directory);
+    }
+
     pub(super) fn view(&self) -> Element<crate::app::Message> {
         let mut list = widget::list_column();
 
-        let vm_name = self.vm_name.as_deref().or(self.default_vm_name.as_deref());
+        let vm_name = self.vm_name();
         let vm_name_row = {
             let vm_name_text = widget::text("VM Name:  ");
 
@@ -232,7 +668,21 @@ impl OptionSelection {
                 .push(vm_name_text)
                 .push(vm_name_input)
         };
-        list = list.add(vm_name_row);
+        let name_issue_row = self.name_issue.as_deref().map(|issue| {
+            widget::row()
+                .align_y(Alignment::Center)
+                .spacing(cosmic::theme::active().cosmic().spacing.space_s)
+                .push(widget::text::caption(issue).class(cosmic::style::Text::Accent))
+                .push_maybe(self.name_suggestion.as_ref().map(|suggestion| {
+                    widget::button::link(format!("Use {suggestion}"))
+                        .on_press(Message::SelectedVMName(suggestion.clone()).into())
+                }))
+        });
+        list = list.add(
+            widget::column()
+                .push(vm_name_row)
+                .push_maybe(name_issue_row),
+        );
 
         let os_row = {
             let mut row = widget::row();
@@ -252,56 +702,52 @@ impl OptionSelection {
                 .widget("Architecture", |arch| Message::SelectedArch(arch).into());
             row.push_maybe(arch_dropdown)
         };
-        list = list.add(os_row);
+        let selection_issue = self
+            .selection_issue()
+            .map(|issue| widget::text::caption(issue).class(cosmic::style::Text::Accent));
+        list = list.add(widget::column().push(os_row).push_maybe(selection_issue));
 
-        let cpu_row = {
-            let cpu_text = widget::text("CPU Cores:  ");
-            let cpu_slider = widget::slider(1.0..=*TOTAL_CPU_CORES, self.cpu_cores as f64, |x| {
-                Message::SetCPUCores(x as usize).into()
+        list = list.add(cpu_cores_row(self.cpu_cores, |cores| {
+            Message::SetCPUCores(cores).into()
+        }));
+        list = list.add(ram_row(self.ram, |ram| Message::SetRAM(ram).into()));
+        if self.creates_disk() {
+            list = list.add(self.disk_view());
+        }
+        let directory_issue_row = self
+            .directory_issue
+            .clone()
+            .or_else(|| self.directory_error.clone())
+            .map(|issue| {
+                let create = (!self.directory.exists()).then(|| {
+                    widget::button::link("Create Directory").on_press(Message::CreateVMDir.into())
+                });
+                widget::row()
+                    .align_y(Alignment::Center)
+                    .spacing(cosmic::theme::active().cosmic().spacing.space_s)
+                    .push(widget::text::caption(issue).class(cosmic::style::Text::Accent))
+                    .push_maybe(create)
             });
-            let selected_cpu_text = widget::text(format!("  {}", self.cpu_cores));
-            widget::row()
-                .align_y(Alignment::Center)
-                .push(cpu_text)
-                .push(cpu_slider)
-                .push(selected_cpu_text)
-        };
-        list = list.add(cpu_row);
-
-        let ram_row = {
-            let ram_text = widget::text("RAM:  ");
-            let ram_slider = widget::slider(
-                100.0 * size::consts::MiB as f64..=*TOTAL_RAM,
-                self.ram,
-                |x| Message::SetRAM(x).into(),
-            )
-            .step(0.0001);
-            let selected_ram_text = widget::text(format!("  {}", size::Size::from_bytes(self.ram)));
-
-            widget::row()
-                .align_y(Alignment::Center)
-                .push(ram_text)
-                .push(ram_slider)
-                .push(selected_ram_text)
-        };
-        list = list.add(ram_row);
-
-        let dir_row = {
-            let dir_text = widget::text("VM Directory:  ");
-            let dir_input =
-                widget::text_input("VM Directory", self.directory.display().to_string())
-                    .on_input(|dir| Message::SelectedVMDir(PathBuf::from(dir)).into());
-            let dir_open_button = widget::button::icon(icon::from_name("folder-open-symbolic"))
-                .on_press(Message::SelectVMDir.into())
-                .tooltip("Select VM Directory");
-
-            widget::row()
-                .align_y(Alignment::Center)
-                .push(dir_text)
-                .push(dir_input)
-                .push(dir_open_button)
-        };
-        list = list.add(dir_row);
+        list = list.add(
+            widget::column()
+                .push(directory_row(
+                    &self.directory,
+                    |dir| Message::SelectedVMDir(dir).into(),
+                    Message::SelectVMDir.into(),
+                ))
+                .push_maybe(directory_issue_row),
+        );
+        list = list.add(shared_dir_row(
+            self.shared_dir.as_deref(),
+            // Custom images are most likely Linux appliances
+            crate::vm::shared_dir_mount_command(self.os_name().unwrap_or("linux")),
+            Message::SelectSharedDir.into(),
+            Message::ClearSharedDir.into(),
+        ));
+        list = list.add(self.advanced_view());
+        if matches!(self.source, Source::Catalog(_)) {
+            list = list.add(self.preset_view());
+        }
 
         list = list.add(widget::vertical_space());
 
@@ -312,10 +758,20 @@ impl OptionSelection {
                 .on_press(super::Message::ChangePage(Page::SelectOS.into()).into());
             row = row.push(back);
 
-            let next = widget::button::suggested("Next");
+            // Custom images have nothing to download, so the VM is created straight away
+            let (next, next_message): (_, fn(String) -> super::Message) = match self.source {
+                Source::Catalog(_) => (
+                    widget::button::suggested("Next"),
+                    super::Message::StartDownloads,
+                ),
+                Source::CustomImage(_) => (
+                    widget::button::suggested("Create"),
+                    super::Message::CreateFromImage,
+                ),
+            };
             let next = match vm_name {
-                Some(vm_name) if self.can_go_next(vm_name) => {
-                    next.on_press(super::Message::StartDownloads(vm_name.to_owned()).into())
+                Some(vm_name) if self.can_go_next() => {
+                    next.on_press(next_message(vm_name.to_owned()).into())
                 }
                 _ => next,
             };
@@ -331,21 +787,336 @@ impl OptionSelection {
         list.into()
     }
 
-    fn can_go_next(&self, vm_name: &str) -> bool {
-        !vm_name.is_empty()
-            && !vm_name.contains('/')
-            && self.default_vm_name.is_some()
-            && self.directory.exists()
-            && !self.directory.join(vm_name).exists()
+    fn disk_view(&self) -> Element<crate::app::Message> {
+        let disk_size = self.disk_size();
+        let range = self.min_disk_size()..=MAX_DISK_SIZE;
+        let on_change = |size| crate::app::Message::from(Message::SetDiskSize(size));
+        let size_row = match disk_size {
+            Some(disk_size) => size_row("Disk Size:  ", range, disk_size, on_change),
+            // The default size depends on quickemu, so it isn't shown until a size is chosen
+            None => widget::row()
+                .align_y(Alignment::Center)
+                .push(widget::text("Disk Size:  "))
+                .push(widget::slider(range.clone(), *range.start(), on_change).step(0.0001))
+                .push(widget::text("  Quickemu default"))
+                .into(),
+        };
+        let reset_button = widget::button::icon(icon::from_name("edit-undo-symbolic"))
+            .on_press_maybe(disk_size.is_some().then(|| Message::ResetDiskSize.into()))
+            .tooltip("Use Quickemu's Default Size");
+        let size_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(size_row)
+            .push(reset_button);
+
+        let reserves_space = self
+            .preallocation
+            .is_some_and(Preallocation::reserves_space);
+        let free_space = match self.free_space {
+            Some(free) if reserves_space && disk_size.is_some_and(|size| free < size as u64) => {
+                widget::text::caption(format!(
+                    "Only {} is free in the VM directory, which isn't enough to preallocate \
+                     the disk",
+                    size::Size::from_bytes(free)
+                ))
+                .class(cosmic::style::Text::Accent)
+            }
+            Some(free) if reserves_space => widget::text::caption(format!(
+                "{} free in the VM directory",
+                size::Size::from_bytes(free)
+            )),
+            Some(free) => widget::text::caption(format!(
+                "{} free in the VM directory. The disk only takes up space as it's written to.",
+                size::Size::from_bytes(free)
+            )),
+            None => widget::text::caption("The free space in the VM directory is unknown"),
+        };
+
+        widget::column().push(size_row).push(free_space).into()
+    }
+
+    pub(super) fn preset_saved(&mut self, name: &str, replaced: bool) {
+        self.preset_status = Some(if replaced {
+            format!("Replaced the {name} preset")
+        } else {
+            format!("Saved the {name} preset")
+        });
+    }
+
+    fn preset_view(&self) -> Element<crate::app::Message> {
+        let name_input = widget::text_input("Preset Name", self.preset_name.as_str())
+            .on_input(|name| Message::SetPresetName(name).into())
+            .on_submit(Message::SavePreset.into());
+        let latest_release =
+            widget::checkbox("Always use the latest release", self.preset_latest_release)
+                .on_toggle(|latest| Message::SetPresetLatestRelease(latest).into());
+        let save = widget::button::standard("Save as Preset")
+            .on_press_maybe(self.to_preset().map(|_| Message::SavePreset.into()));
+
+        let row = widget::row()
+            .align_y(Alignment::Center)
+            .spacing(cosmic::theme::active().cosmic().spacing.space_s)
+            .push(name_input)
+            .push(latest_release)
+            .push(save);
+        widget::column()
+            .push(row)
+            .push_maybe(self.preset_status.as_deref().map(widget::text::caption))
+            .into()
+    }
+
+    fn advanced_view(&self) -> Element<crate::app::Message> {
+        let toggle_icon = if self.show_advanced {
+            "go-down-symbolic"
+        } else {
+            "go-next-symbolic"
+        };
+        let header = widget::row()
+            .align_y(Alignment::Center)
+            .push(widget::text::heading("Advanced"))
+            .push(
+                widget::button::icon(icon::from_name(toggle_icon))
+                    .on_press(Message::ToggleAdvanced.into())
+                    .tooltip("Advanced Options"),
+            );
+        if !self.show_advanced {
+            return header.into();
+        }
+
+        // Quickget picks these based on the OS, so they're left to it unless changed
+        let uefi = self.bios_supported().then(|| {
+            toggle_row("UEFI Firmware:  ", self.uefi, |uefi| {
+                Message::SetUefi(uefi).into()
+            })
+        });
+        let secure_boot = self.secure_boot_supported().then(|| {
+            toggle_row("Secure Boot:  ", self.secure_boot, |secure_boot| {
+                Message::SetSecureBoot(secure_boot).into()
+            })
+        });
+        let tpm = self.tpm_supported().then(|| {
+            toggle_row("TPM Emulation:  ", self.tpm, |tpm| {
+                Message::SetTpm(tpm).into()
+            })
+        });
+        let tpm_note = (!*SWTPM_INSTALLED)
+            .then(|| widget::text::caption("TPM emulation requires swtpm to be installed"));
+        let firmware_note = if !self.bios_supported() {
+            Some(widget::text::caption(
+                "The selected OS and architecture require UEFI firmware",
+            ))
+        } else if self.uefi.is_none() && self.secure_boot == Some(true) {
+            Some(widget::text::caption("Secure Boot uses UEFI firmware"))
+        } else {
+            None
+        };
+
+        let preallocation = self.creates_disk().then(|| {
+            let current = self.preallocation.unwrap_or_default();
+            [
+                Preallocation::Off,
+                Preallocation::Metadata,
+                Preallocation::Falloc,
+                Preallocation::Full,
+            ]
+            .into_iter()
+            .fold(
+                widget::row()
+                    .align_y(Alignment::Center)
+                    .spacing(cosmic::theme::active().cosmic().spacing.space_s)
+                    .push(widget::text("Disk Preallocation:  ")),
+                |row, preallocation| {
+                    row.push(widget::radio(
+                        preallocation.to_string(),
+                        preallocation,
+                        Some(current),
+                        |preallocation| Message::SetPreallocation(preallocation).into(),
+                    ))
+                },
+            )
+        });
+
+        widget::column()
+            .push(header)
+            .push_maybe(preallocation)
+            .push_maybe(uefi)
+            .push_maybe(firmware_note)
+            .push_maybe(secure_boot)
+            .push_maybe(tpm)
+            .push_maybe(tpm_note)
+            .into()
     }
-}
 
-fn default_vm_name(os: &OS, release: &str, edition: Option<&str>, arch: Arch) -> String {
-    let mut vm_name = format!("{}-{}", os.name, release);
-    if let Some(edition) = edition {
-        vm_name.push('-');
-        vm_name.push_str(edition)
+    fn can_go_next(&self) -> bool {
+        self.selection_issue().is_none()
+            && self.directory_issue.is_none()
+            && self.name_issue.is_none()
     }
+}
+
+/// Slider selecting the number of CPU cores given to a VM.
+pub(crate) fn cpu_cores_row<'a>(
+    cpu_cores: usize,
+    on_change: impl Fn(usize) -> crate::app::Message + 'a,
+) -> Element<'a, crate::app::Message> {
+    let cpu_text = widget::text("CPU Cores:  ");
+    let cpu_slider = widget::slider(1.0..=*TOTAL_CPU_CORES, cpu_cores as f64, move |x| {
+        on_change(x as usize)
+    });
+    let selected_cpu_text = widget::text(format!("  {}", cpu_cores));
+    widget::row()
+        .align_y(Alignment::Center)
+        .push(cpu_text)
+        .push(cpu_slider)
+        .push(selected_cpu_text)
+        .into()
+}
+
+/// Slider selecting the amount of RAM, in bytes, given to a VM.
+pub(crate) fn ram_row<'a>(
+    ram: f64,
+    on_change: impl Fn(f64) -> crate::app::Message + 'a,
+) -> Element<'a, crate::app::Message> {
+    size_row(
+        "RAM:  ",
+        100.0 * size::consts::MiB as f64..=*TOTAL_RAM,
+        ram,
+        on_change,
+    )
+}
+
+/// Slider selecting a size in bytes, displayed in human readable units.
+pub(crate) fn size_row<'a>(
+    label: &'a str,
+    range: RangeInclusive<f64>,
+    value: f64,
+    on_change: impl Fn(f64) -> crate::app::Message + 'a,
+) -> Element<'a, crate::app::Message> {
+    let size_text = widget::text(label);
+    let size_slider = widget::slider(range, value, on_change).step(0.0001);
+    let selected_size_text = widget::text(format!("  {}", size::Size::from_bytes(value)));
+
+    widget::row()
+        .align_y(Alignment::Center)
+        .push(size_text)
+        .push(size_slider)
+        .push(selected_size_text)
+        .into()
+}
+
+/// Radio buttons turning a setting on or off, or leaving it to quickget.
+fn toggle_row<'a>(
+    label: &'a str,
+    value: Option<bool>,
+    on_change: impl Fn(Option<bool>) -> crate::app::Message + Copy + 'a,
+) -> Element<'a, crate::app::Message> {
+    [
+        (None, "Quickget default"),
+        (Some(true), "On"),
+        (Some(false), "Off"),
+    ]
+    .into_iter()
+    .fold(
+        widget::row()
+            .align_y(Alignment::Center)
+            .spacing(cosmic::theme::active().cosmic().spacing.space_s)
+            .push(widget::text(label)),
+        |row, (option, option_label)| {
+            row.push(widget::radio(option_label, option, Some(value), on_change))
+        },
+    )
+    .into()
+}
+
+/// Text input and file chooser button selecting the directory a VM is placed in.
+pub(crate) fn directory_row<'a>(
+    directory: &Path,
+    on_input: impl Fn(PathBuf) -> crate::app::Message + 'a,
+    on_select: crate::app::Message,
+) -> Element<'a, crate::app::Message> {
+    let dir_text = widget::text("VM Directory:  ");
+    let dir_input = widget::text_input("VM Directory", directory.display().to_string())
+        .on_input(move |dir| on_input(PathBuf::from(dir)));
+    let dir_open_button = widget::button::icon(icon::from_name("folder-open-symbolic"))
+        .on_press(on_select)
+        .tooltip("Select VM Directory");
+
+    widget::row()
+        .align_y(Alignment::Center)
+        .push(dir_text)
+        .push(dir_input)
+        .push(dir_open_button)
+        .into()
+}
+
+/// Picker for a host directory shared with the guest, along with the command mounting it there.
+pub(crate) fn shared_dir_row<'a>(
+    shared_dir: Option<&Path>,
+    mount_command: Option<String>,
+    on_select: crate::app::Message,
+    on_clear: crate::app::Message,
+) -> Element<'a, crate::app::Message> {
+    let shared_dir_text = widget::text("Shared Folder:  ");
+    let selected_dir_text = widget::text(
+        shared_dir.map_or_else(|| "None".to_string(), |dir| dir.display().to_string()),
+    )
+    .width(Length::Fill);
+    let select_button = widget::button::icon(icon::from_name("folder-open-symbolic"))
+        .on_press(on_select)
+        .tooltip("Select Shared Folder");
+    let clear_button = widget::button::icon(icon::from_name("edit-clear-symbolic"))
+        .on_press_maybe(shared_dir.is_some().then_some(on_clear))
+        .tooltip("Stop Sharing");
+
+    let row = widget::row()
+        .align_y(Alignment::Center)
+        .push(shared_dir_text)
+        .push(selected_dir_text)
+        .push(select_button)
+        .push(clear_button);
+
+    let mount_row = shared_dir.map(|_| match mount_command {
+        Some(mount_command) => widget::row()
+            .align_y(Alignment::Center)
+            .push(
+                widget::text::caption(mount_command.clone())
+                    .font(cosmic::font::mono())
+                    .width(Length::Fill),
+            )
+            .push(
+                widget::button::icon(icon::from_name("edit-copy-symbolic"))
+                    .on_press(crate::app::Message::CopyToClipboard(mount_command))
+                    .tooltip("Copy Mount Command"),
+            ),
+        None => widget::row().push(widget::text::caption(
+            "Install the SPICE WebDAV daemon in the guest to access the shared folder",
+        )),
+    });
+
+    widget::column().push(row).push_maybe(mount_row).into()
+}
+
+/// Picks the newest release by comparing the numbers within release names. Releases without any,
+/// such as "rolling", are only picked when there's nothing else. Names which can't be told apart
+/// this way, such as those of macOS releases, are taken in the catalog's order, which lists newer
+/// releases first.
+fn latest_release(releases: &[String]) -> Option<&String> {
+    releases
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, release)| {
+            let numbers: Vec<u64> = release
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse().unwrap_or(0))
+                .collect();
+            (numbers, std::cmp::Reverse(*index))
+        })
+        .map(|(_, release)| release)
+}
+
+fn default_vm_name(prefix: &str, arch: Arch) -> String {
+    let mut vm_name = prefix.to_string();
 
     let snake_case_arch: String = arch
         .to_string()
@@ -372,8 +1143,23 @@ pub(crate) enum Message {
     SelectedArch(Arch),
     SetRAM(f64),
     SetCPUCores(usize),
+    SetDiskSize(f64),
+    ResetDiskSize,
+    SetPreallocation(Preallocation),
     SelectVMDir,
     SelectedVMDir(PathBuf),
+    CreateVMDir,
+    CreatedVMDir(Result<(), String>),
+    SelectSharedDir,
+    SelectedSharedDir(PathBuf),
+    ClearSharedDir,
+    ToggleAdvanced,
+    SetUefi(Option<bool>),
+    SetSecureBoot(Option<bool>),
+    SetTpm(Option<bool>),
+    SetPresetName(String),
+    SetPresetLatestRelease(bool),
+    SavePreset,
     SelectedVMName(String),
     FinalizeVMName,
 }
@@ -383,3 +1169,64 @@ impl From<Message> for crate::app::Message {
         crate::app::Message::Creation(super::Message::Options(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn releases(releases: &[&str]) -> Vec<String> {
+        releases.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn secure_boot_turns_on_uefi_by_default() {
+        let mut vm = Vm::with_default_config(PathBuf::from("/vms/vm.toml"));
+        vm.set_firmware(false, false);
+        let customizations = Customizations {
+            secure_boot: Some(true),
+            ..Default::default()
+        };
+        customizations.apply(&mut vm);
+        assert!(vm.uefi());
+        assert!(vm.secure_boot());
+    }
+
+    #[test]
+    fn secure_boot_is_left_off_without_uefi() {
+        let mut vm = Vm::with_default_config(PathBuf::from("/vms/vm.toml"));
+        vm.set_firmware(true, true);
+        let customizations = Customizations {
+            uefi: Some(false),
+            secure_boot: Some(true),
+            ..Default::default()
+        };
+        customizations.apply(&mut vm);
+        assert!(!vm.uefi());
+        assert!(!vm.secure_boot());
+    }
+
+    #[test]
+    fn latest_release_compares_numerically() {
+        let list = releases(&["9.10", "22.04", "24.04", "10.04"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("24.04"));
+        let list = releases(&["9", "10", "11"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("11"));
+    }
+
+    #[test]
+    fn latest_release_prefers_numbered_releases() {
+        let list = releases(&["rolling", "40", "41"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("41"));
+        let list = releases(&["rolling"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("rolling"));
+        assert_eq!(latest_release(&[]), None);
+    }
+
+    #[test]
+    fn latest_release_falls_back_to_catalog_order() {
+        let list = releases(&["sonoma", "ventura", "monterey"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("sonoma"));
+        let list = releases(&["rolling", "stable"]);
+        assert_eq!(latest_release(&list).map(String::as_str), Some("rolling"));
+    }
+}
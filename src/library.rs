@@ -0,0 +1,1147 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+mod clone;
+mod console;
+mod delete;
+mod edit;
+mod export;
+mod import;
+mod import_archive;
+mod launch;
+mod media;
+mod metadata;
+mod network;
+mod resize;
+mod snapshots;
+mod usage;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use cosmic::{
+    app::command::Task,
+    cosmic_config,
+    iced::{
+        alignment::{Horizontal, Vertical},
+        keyboard, Alignment, Length, Subscription,
+    },
+    theme,
+    widget::{self, icon},
+    Apply, Element,
+};
+use size::Size;
+
+use crate::{
+    config::{Config, VmMetadata},
+    vm::Vm,
+};
+
+pub struct State {
+    entries: Vec<Entry>,
+    launches: HashMap<PathBuf, launch::Launch>,
+    consoles: HashMap<PathBuf, console::Console>,
+    /// Only VMs with this tag are listed, when it's set.
+    tag_filter: Option<String>,
+    page: Page,
+}
+
+impl State {
+    pub fn new(
+        config: &mut Config,
+        config_handler: Option<&cosmic_config::Config>,
+    ) -> (Self, Task<crate::app::Message>) {
+        // Duplicates registered before they were dropped on writing are removed once at startup
+        let mut configs = config.existing_vm_configs.clone();
+        dedup_configs(&mut configs);
+        if configs.len() != config.existing_vm_configs.len() {
+            set_vm_configs(config, config_handler, configs);
+        }
+
+        let state = Self {
+            entries: vec![],
+            launches: HashMap::new(),
+            consoles: HashMap::new(),
+            tag_filter: None,
+            page: Page::default(),
+        };
+        (state, load_entries(config))
+    }
+
+    pub fn refresh(&mut self, config: &Config) -> Task<crate::app::Message> {
+        load_entries(config)
+    }
+
+    /// Polls the status of every VM launched by the app, and reads from attached serial consoles.
+    /// While a console is shown, key presses are sent to it.
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let keys = match self.page {
+            Page::Console(_) => keyboard::on_key_press(|key, modifiers| {
+                console::key_input(key, modifiers).map(|bytes| Message::ConsoleInput(bytes).into())
+            }),
+            _ => Subscription::none(),
+        };
+        Subscription::batch([
+            launch::subscription(self.launches.iter()),
+            console::subscription(self.consoles.iter()),
+            keys,
+        ])
+    }
+
+    pub fn update(
+        &mut self,
+        msg: Message,
+        config: &mut Config,
+        config_handler: Option<&cosmic_config::Config>,
+    ) -> Task<crate::app::Message> {
+        match msg {
+            Message::Refresh => return self.refresh(config),
+            Message::Loaded(entries) => {
+                self.entries = entries;
+                if self
+                    .tag_filter
+                    .as_ref()
+                    .is_some_and(|tag| !self.entries.iter().any(|entry| entry.has_tag(tag)))
+                {
+                    self.tag_filter = None;
+                }
+                if matches!(self.page, Page::Loading) {
+                    self.page = Page::List;
+                }
+            }
+            Message::LocateVM(config_path) => {
+                return Task::perform(
+                    crate::app::select_file("Locate VM Config"),
+                    move |new_path| {
+                        match new_path {
+                            Some(new_path) => crate::app::Message::from(Message::Located {
+                                old_config_path: config_path,
+                                new_config_path: new_path,
+                            }),
+                            None => crate::app::Message::None,
+                        }
+                        .into()
+                    },
+                );
+            }
+            Message::Located {
+                old_config_path,
+                new_config_path,
+            } => {
+                let already_registered = config.existing_vm_configs.contains(&new_config_path);
+                let configs = config
+                    .existing_vm_configs
+                    .iter()
+                    .filter_map(|path| {
+                        if *path != old_config_path {
+                            Some(path.clone())
+                        } else if already_registered {
+                            None
+                        } else {
+                            Some(new_config_path.clone())
+                        }
+                    })
+                    .collect();
+                if !already_registered {
+                    move_vm_metadata(config, config_handler, &old_config_path, new_config_path);
+                }
+                set_vm_configs(config, config_handler, configs);
+                return self.refresh(config);
+            }
+            Message::RemoveEntry(config_path) => {
+                let configs = config
+                    .existing_vm_configs
+                    .iter()
+                    .filter(|path| **path != config_path)
+                    .cloned()
+                    .collect();
+                set_vm_configs(config, config_handler, configs);
+                return self.refresh(config);
+            }
+            Message::StartVM(config_path) => {
+                if self.is_running(&config_path)
+                    || !linked_clones(&self.entries, &config_path).is_empty()
+                {
+                    return Task::none();
+                }
+                // The console is attached before QEMU starts, so that no boot output is missed
+                if let Some(vm) = self.vm(&config_path) {
+                    let console = console::Console::new(vm);
+                    self.consoles
+                        .entry(config_path.clone())
+                        .or_insert(console)
+                        .reset();
+                }
+                let (launch, task) = launch::Launch::start(config_path.clone());
+                self.launches.insert(config_path, launch);
+                return task;
+            }
+            Message::Launch(config_path, event) => {
+                if let Some(launch) = self.launches.get_mut(&config_path) {
+                    launch.update(event);
+                    if !launch.is_active() {
+                        if let Some(console) = self.consoles.get_mut(&config_path) {
+                            console.detach();
+                        }
+                    }
+                }
+            }
+            Message::ShowConsole(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let console = console::Console::new(vm);
+                    self.consoles
+                        .entry(config_path.clone())
+                        .or_insert(console)
+                        .attach();
+                    self.page = Page::Console(config_path);
+                }
+            }
+            Message::Console(config_path, msg) => {
+                if let Some(console) = self.consoles.get_mut(&config_path) {
+                    return console.update(msg);
+                }
+            }
+            Message::ConsoleInput(bytes) => {
+                if let Page::Console(ref config_path) = self.page {
+                    if let Some(console) = self.consoles.get(config_path) {
+                        console.send(bytes);
+                    }
+                }
+            }
+            Message::Control(config_path, action) => {
+                if let Some(launch) = self.launches.get(&config_path) {
+                    return launch.control(config_path, action);
+                }
+            }
+            Message::OpenDir(dir) => {
+                if let Err(e) = open::that_detached(&dir) {
+                    eprintln!("failed to open {dir:?}: {e}");
+                }
+            }
+            Message::ShowList => self.page = Page::List,
+            Message::FilterTag(tag) => self.tag_filter = tag,
+            Message::EditVM(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    self.page = Page::Edit(edit::EditVm::new(vm.clone()));
+                }
+            }
+            Message::Edit(msg) => {
+                // Results of background work may arrive after the page was left
+                if let Page::Edit(ref mut edit) = self.page {
+                    if matches!(msg, edit::Message::Save)
+                        && (is_running(&self.launches, edit.config_path())
+                            || (edit.moves()
+                                && !linked_clones(&self.entries, edit.config_path()).is_empty()))
+                    {
+                        return Task::none();
+                    }
+                    return edit.update(msg);
+                }
+            }
+            Message::EditMetadata(config_path) => {
+                if let Some(entry) = self.entries.iter().find(|entry| entry.path == config_path) {
+                    let vm_name = entry.vm.as_ref().map_or_else(
+                        |_| config_path.display().to_string(),
+                        |vm| vm.name().to_string(),
+                    );
+                    self.page = Page::Metadata(metadata::EditMetadata::new(
+                        config_path,
+                        vm_name,
+                        &entry.metadata,
+                    ));
+                }
+            }
+            Message::Metadata(msg) => {
+                if let Page::Metadata(ref mut metadata) = self.page {
+                    return metadata.update(msg);
+                }
+            }
+            Message::MetadataEdited {
+                config_path,
+                metadata,
+            } => {
+                let mut vm_metadata = config.vm_metadata.clone();
+                if metadata.is_empty() {
+                    vm_metadata.remove(&config_path);
+                } else {
+                    vm_metadata.insert(config_path, metadata);
+                }
+                set_vm_metadata(config, config_handler, vm_metadata);
+                self.page = Page::List;
+                return self.refresh(config);
+            }
+            Message::EditMedia(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    self.page = Page::Media(media::Media::new(vm.clone()));
+                }
+            }
+            Message::Media(msg) => {
+                if let Page::Media(ref mut media) = self.page {
+                    if matches!(msg, media::Message::Save)
+                        && is_running(&self.launches, media.config_path())
+                    {
+                        return Task::none();
+                    }
+                    return media.update(msg);
+                }
+            }
+            Message::EditPortForwards(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let other_vms = self
+                        .entries
+                        .iter()
+                        .filter(|entry| entry.path != config_path)
+                        .filter_map(|entry| entry.vm.as_ref().ok())
+                        .map(|vm| (vm.name().to_string(), vm.port_forwards()))
+                        .collect();
+                    let port_forwards = network::PortForwards::new(vm.clone(), other_vms);
+                    self.page = Page::PortForwards(port_forwards);
+                }
+            }
+            Message::PortForwards(msg) => {
+                if let Page::PortForwards(ref mut port_forwards) = self.page {
+                    if is_running(&self.launches, port_forwards.config_path()) {
+                        match msg {
+                            network::Message::Save => return Task::none(),
+                            // The config is only written once the ports have been checked, by
+                            // which time the VM may have been started
+                            network::Message::Checked(..) => {
+                                return port_forwards.update(network::Message::Error(
+                                    "The VM was started, so the port forwards weren't saved"
+                                        .to_string(),
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+                    return port_forwards.update(msg);
+                }
+            }
+            Message::CloneVM(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    self.page = Page::Clone(clone::CloneVm::new(vm.clone()));
+                }
+            }
+            Message::Clone(msg) => {
+                if let Page::Clone(ref mut clone) = self.page {
+                    if matches!(msg, clone::Message::Clone)
+                        && is_running(&self.launches, clone.config_path())
+                    {
+                        return Task::none();
+                    }
+                    return clone.update(msg);
+                }
+            }
+            Message::VMCloned(config_path) | Message::VMImported(config_path) => {
+                let mut configs = config.existing_vm_configs.clone();
+                configs.push(config_path);
+                set_vm_configs(config, config_handler, configs);
+                self.page = Page::List;
+                return self.refresh(config);
+            }
+            Message::ShowSnapshots(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let (snapshots, task) = snapshots::Snapshots::new(vm.clone());
+                    self.page = Page::Snapshots(snapshots);
+                    return task;
+                }
+            }
+            Message::Snapshots(msg) => {
+                if let Page::Snapshots(ref mut snapshots) = self.page {
+                    if let snapshots::Message::Request(snapshots::Operation::Revert(_))
+                    | snapshots::Message::Run(snapshots::Operation::Revert(_)) = msg
+                    {
+                        if !linked_clones(&self.entries, snapshots.config_path()).is_empty() {
+                            return Task::none();
+                        }
+                    }
+                    let qmp_socket = self
+                        .launches
+                        .get(snapshots.config_path())
+                        .and_then(launch::Launch::qmp_socket)
+                        .cloned();
+                    return snapshots.update(msg, qmp_socket);
+                }
+            }
+            Message::ResizeDisk(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let (resize, task) = resize::ResizeDisk::new(vm.clone());
+                    self.page = Page::Resize(resize);
+                    return task;
+                }
+            }
+            Message::Resize(msg) => {
+                if let Page::Resize(ref mut resize) = self.page {
+                    if matches!(msg, resize::Message::Resize)
+                        && (is_running(&self.launches, resize.config_path())
+                            || !linked_clones(&self.entries, resize.config_path()).is_empty())
+                    {
+                        return Task::none();
+                    }
+                    return resize.update(msg);
+                }
+            }
+            Message::ShowUsage => {
+                let vms = self
+                    .entries
+                    .iter()
+                    .filter_map(|entry| entry.vm.as_ref().ok())
+                    .cloned()
+                    .collect();
+                let (usage, task) = usage::StorageUsage::new(vms, config.default_vm_dir.clone());
+                self.page = Page::Usage(usage);
+                return task;
+            }
+            Message::Usage(msg) => {
+                if let Page::Usage(ref mut usage) = self.page {
+                    return usage.update(msg);
+                }
+            }
+            Message::ImportVMs => {
+                let (import, task) = import::ImportVms::new();
+                self.page = Page::Import(import);
+                return task;
+            }
+            Message::Import(import::Message::Import) => {
+                if let Page::Import(ref import) = self.page {
+                    let mut configs = config.existing_vm_configs.clone();
+                    configs.extend(import.selected_configs());
+                    set_vm_configs(config, config_handler, configs);
+                    self.page = Page::List;
+                    return self.refresh(config);
+                }
+            }
+            Message::Import(msg) => {
+                if let Page::Import(ref mut import) = self.page {
+                    return import.update(msg, &config.existing_vm_configs);
+                }
+            }
+            Message::ImportArchive(msg) => {
+                if let Page::ImportArchive(ref mut import) = self.page {
+                    return import.update(msg);
+                }
+            }
+            Message::ShowImportArchive => {
+                let (import, task) =
+                    import_archive::ImportArchive::new(config.default_vm_dir.clone());
+                self.page = Page::ImportArchive(import);
+                return task;
+            }
+            Message::ExportVM(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let (export, task) = export::ExportVm::new(vm.clone());
+                    self.page = Page::Export(export);
+                    return task;
+                }
+            }
+            Message::Export(msg) => {
+                if let Page::Export(ref mut export) = self.page {
+                    if matches!(msg, export::Message::Export)
+                        && is_running(&self.launches, export.config_path())
+                    {
+                        return Task::none();
+                    }
+                    return export.update(msg);
+                }
+            }
+            Message::DeleteVM(config_path) => {
+                if let Some(vm) = self.vm(&config_path) {
+                    let (delete, task) = delete::DeleteVm::new(vm.clone());
+                    self.page = Page::Delete(delete);
+                    return task;
+                }
+            }
+            Message::Delete(msg) => {
+                if let Page::Delete(ref mut delete) = self.page {
+                    if matches!(msg, delete::Message::Delete(_))
+                        && (is_running(&self.launches, delete.config_path())
+                            || !linked_clones(&self.entries, delete.config_path()).is_empty())
+                    {
+                        return Task::none();
+                    }
+                    return delete.update(msg);
+                }
+            }
+            Message::VMDeleted(config_path) => {
+                let configs = config
+                    .existing_vm_configs
+                    .iter()
+                    .filter(|path| **path != config_path)
+                    .cloned()
+                    .collect();
+                set_vm_configs(config, config_handler, configs);
+                self.launches.remove(&config_path);
+                self.consoles.remove(&config_path);
+                self.page = Page::List;
+                return self.refresh(config);
+            }
+            Message::VMEdited {
+                old_config_path,
+                new_config_path,
+            } => {
+                if old_config_path != new_config_path {
+                    let configs = config
+                        .existing_vm_configs
+                        .iter()
+                        .map(|path| {
+                            if *path == old_config_path {
+                                new_config_path.clone()
+                            } else {
+                                path.clone()
+                            }
+                        })
+                        .collect();
+                    move_vm_metadata(config, config_handler, &old_config_path, new_config_path);
+                    set_vm_configs(config, config_handler, configs);
+                }
+                self.page = Page::List;
+                return self.refresh(config);
+            }
+        }
+        Task::none()
+    }
+
+    fn vm(&self, config_path: &Path) -> Option<&Vm> {
+        self.entries
+            .iter()
+            .find(|entry| entry.path == config_path)
+            .and_then(|entry| entry.vm.as_ref().ok())
+    }
+
+    fn is_running(&self, config_path: &Path) -> bool {
+        is_running(&self.launches, config_path)
+    }
+
+    pub fn view(&self) -> Element<crate::app::Message> {
+        match self.page {
+            Page::Loading => widget::text("Loading")
+                .apply(widget::container)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into(),
+            Page::List if self.entries.is_empty() => widget::column()
+                .push(widget::text("No VMs have been created yet"))
+                .push(
+                    widget::button::standard("Import Existing VMs")
+                        .on_press(Message::ImportVMs.into()),
+                )
+                .push(
+                    widget::button::standard("Import VM Archive")
+                        .on_press(Message::ShowImportArchive.into()),
+                )
+                .align_x(Alignment::Center)
+                .spacing(theme::active().cosmic().spacing.space_s)
+                .apply(widget::container)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into(),
+            Page::List => {
+                let header_row = widget::row()
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::icon(icon::from_name("drive-harddisk-symbolic"))
+                            .on_press(Message::ShowUsage.into())
+                            .tooltip("Storage Usage"),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("document-import-symbolic"))
+                            .on_press(Message::ImportVMs.into())
+                            .tooltip("Import Existing VMs"),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("package-x-generic-symbolic"))
+                            .on_press(Message::ShowImportArchive.into())
+                            .tooltip("Import VM Archive"),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("view-refresh-symbolic"))
+                            .on_press(Message::Refresh.into())
+                            .tooltip("Check VMs"),
+                    );
+
+                let broken_entries = self
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.vm.is_err())
+                    .count();
+                let warning = (broken_entries > 0).then(|| {
+                    widget::text(format!(
+                        "{broken_entries} registered VMs could not be found or read"
+                    ))
+                    .class(cosmic::style::Text::Accent)
+                });
+
+                let tags: BTreeSet<&str> = self
+                    .entries
+                    .iter()
+                    .flat_map(|entry| entry.metadata.tags.iter().map(String::as_str))
+                    .collect();
+                let tag_row = (!tags.is_empty()).then(|| {
+                    let tag_button = |label: &str, tag: Option<&str>| {
+                        let button = if self.tag_filter.as_deref() == tag {
+                            widget::button::suggested(label.to_string())
+                        } else {
+                            widget::button::standard(label.to_string())
+                        };
+                        button.on_press(Message::FilterTag(tag.map(ToOwned::to_owned)).into())
+                    };
+                    tags.iter().fold(
+                        widget::row()
+                            .spacing(theme::active().cosmic().spacing.space_xxs)
+                            .push(tag_button("All", None)),
+                        |row, tag| row.push(tag_button(tag, Some(tag))),
+                    )
+                });
+
+                let list_column = self
+                    .entries
+                    .iter()
+                    .filter(|entry| {
+                        self.tag_filter
+                            .as_ref()
+                            .is_none_or(|tag| entry.has_tag(tag))
+                    })
+                    .fold(
+                        widget::list_column().style(theme::Container::ContextDrawer),
+                        |list, entry| {
+                            list.add(entry.view(
+                                self.launches.get(&entry.path),
+                                &linked_clones(&self.entries, &entry.path),
+                            ))
+                        },
+                    );
+
+                widget::column()
+                    .push(header_row)
+                    .push_maybe(tag_row)
+                    .push_maybe(warning)
+                    .push(widget::scrollable(list_column))
+                    .into()
+            }
+            Page::Console(ref config_path) => match self.consoles.get(config_path) {
+                Some(console) => console.view(),
+                None => widget::text("This VM has no serial console").into(),
+            },
+            Page::Edit(ref edit) => edit.view(
+                self.is_running(edit.config_path()),
+                &linked_clones(&self.entries, edit.config_path()),
+            ),
+            Page::Metadata(ref metadata) => metadata.view(),
+            Page::Media(ref media) => media.view(self.is_running(media.config_path())),
+            Page::PortForwards(ref port_forwards) => {
+                port_forwards.view(self.is_running(port_forwards.config_path()))
+            }
+            Page::Snapshots(ref snapshots) => {
+                snapshots.view(&linked_clones(&self.entries, snapshots.config_path()))
+            }
+            Page::Resize(ref resize) => resize.view(
+                self.is_running(resize.config_path()),
+                &linked_clones(&self.entries, resize.config_path()),
+            ),
+            Page::Usage(ref usage) => usage.view(),
+            Page::Import(ref import) => import.view(),
+            Page::ImportArchive(ref import) => import.view(),
+            Page::Export(ref export) => export.view(self.is_running(export.config_path())),
+            Page::Clone(ref clone) => clone.view(self.is_running(clone.config_path())),
+            Page::Delete(ref delete) => delete.view(
+                self.is_running(delete.config_path()),
+                &linked_clones(&self.entries, delete.config_path()),
+            ),
+        }
+    }
+}
+
+/// Takes the launches rather than the whole state, so that it can be called while a page is
+/// borrowed.
+fn is_running(launches: &HashMap<PathBuf, launch::Launch>, config_path: &Path) -> bool {
+    launches
+        .get(config_path)
+        .is_some_and(launch::Launch::is_active)
+}
+
+/// Registers the given configs. Duplicates, which may be registered under different paths, are
+/// dropped here so that the stored list never holds them.
+pub(crate) fn set_vm_configs(
+    config: &mut Config,
+    config_handler: Option<&cosmic_config::Config>,
+    mut configs: Vec<PathBuf>,
+) {
+    dedup_configs(&mut configs);
+
+    match config_handler {
+        Some(config_handler) => {
+            if let Err(e) = config.set_existing_vm_configs(config_handler, configs) {
+                eprintln!("error updating config: {e}");
+            }
+        }
+        None => config.existing_vm_configs = configs,
+    }
+
+    // Metadata of VMs which are no longer registered is dropped
+    if config
+        .vm_metadata
+        .keys()
+        .any(|path| !config.existing_vm_configs.contains(path))
+    {
+        let vm_metadata = config
+            .vm_metadata
+            .iter()
+            .filter(|(path, _)| config.existing_vm_configs.contains(path))
+            .map(|(path, metadata)| (path.clone(), metadata.clone()))
+            .collect();
+        set_vm_metadata(config, config_handler, vm_metadata);
+    }
+}
+
+/// Drops configs which are already in the list, possibly under a different path.
+fn dedup_configs(configs: &mut Vec<PathBuf>) {
+    let mut seen = HashSet::new();
+    configs.retain(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())));
+}
+
+pub(crate) fn set_vm_metadata(
+    config: &mut Config,
+    config_handler: Option<&cosmic_config::Config>,
+    vm_metadata: BTreeMap<PathBuf, VmMetadata>,
+) {
+    match config_handler {
+        Some(config_handler) => {
+            if let Err(e) = config.set_vm_metadata(config_handler, vm_metadata) {
+                eprintln!("error updating config: {e}");
+            }
+        }
+        None => config.vm_metadata = vm_metadata,
+    }
+}
+
+/// Keeps the metadata of a VM whose config has moved.
+fn move_vm_metadata(
+    config: &mut Config,
+    config_handler: Option<&cosmic_config::Config>,
+    old_config_path: &Path,
+    new_config_path: PathBuf,
+) {
+    let mut vm_metadata = config.vm_metadata.clone();
+    if let Some(metadata) = vm_metadata.remove(old_config_path) {
+        vm_metadata.insert(new_config_path, metadata);
+        set_vm_metadata(config, config_handler, vm_metadata);
+    }
+}
+
+fn load_entries(config: &Config) -> Task<crate::app::Message> {
+    let config_paths = config.existing_vm_configs.clone();
+    let vm_metadata = config.vm_metadata.clone();
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                config_paths
+                    .into_iter()
+                    .map(|path| {
+                        let vm = if path.is_file() {
+                            Vm::load(path.clone()).map_err(EntryError::Unreadable)
+                        } else {
+                            Err(EntryError::Missing)
+                        };
+                        let (disks, backing_files) =
+                            vm.as_ref().map(disk_chain).unwrap_or_default();
+                        Entry {
+                            vm,
+                            metadata: vm_metadata.get(&path).cloned().unwrap_or_default(),
+                            disks,
+                            backing_files,
+                            path,
+                        }
+                    })
+                    .collect()
+            })
+            .await
+            .expect("Couldn't spawn thread")
+        },
+        |entries| crate::app::Message::from(Message::Loaded(entries)).into(),
+    )
+}
+
+/// The VM's existing disk images, and the images they're backed by. Paths are canonicalized so
+/// that images referred to in different ways can be matched up.
+fn disk_chain(vm: &Vm) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let canonical = |path: PathBuf| path.canonicalize().unwrap_or(path);
+    let disks: Vec<PathBuf> = vm
+        .disk_images()
+        .into_iter()
+        .filter(|disk| disk.is_file())
+        .collect();
+    let backing_files = disks
+        .iter()
+        .filter_map(|disk| {
+            crate::qemu_img::info(disk)
+                .inspect_err(|e| eprintln!("failed to read {disk:?}: {e}"))
+                .ok()?
+                .full_backing_filename
+        })
+        .map(canonical)
+        .collect();
+    (disks.into_iter().map(canonical).collect(), backing_files)
+}
+
+/// Names of the VMs with disk images backed by those of the given VM. Writing to the base images
+/// would corrupt these linked clones.
+///
+/// Takes the entries rather than the whole state, so that it can be called while a page is
+/// borrowed.
+fn linked_clones(entries: &[Entry], config_path: &Path) -> Vec<String> {
+    let Some(base) = entries.iter().find(|entry| entry.path == config_path) else {
+        return vec![];
+    };
+    entries
+        .iter()
+        .filter(|entry| {
+            entry.path != config_path
+                && entry
+                    .backing_files
+                    .iter()
+                    .any(|backing_file| base.disks.contains(backing_file))
+        })
+        .map(|entry| match &entry.vm {
+            Ok(vm) => vm.name().to_string(),
+            Err(_) => entry.path.display().to_string(),
+        })
+        .collect()
+}
+
+/// A registered config path, alongside the VM parsed from it.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    path: PathBuf,
+    vm: Result<Vm, EntryError>,
+    metadata: VmMetadata,
+    /// Canonical paths of the VM's disk images.
+    disks: Vec<PathBuf>,
+    /// Canonical paths of the images the VM's disk images are backed by.
+    backing_files: Vec<PathBuf>,
+}
+
+/// Reasons a registered VM can't be shown, typically because it was changed outside the app.
+#[derive(Debug, Clone)]
+enum EntryError {
+    Missing,
+    Unreadable(String),
+}
+
+impl Entry {
+    fn has_tag(&self, tag: &str) -> bool {
+        self.metadata.tags.iter().any(|t| t == tag)
+    }
+
+    fn view(
+        &self,
+        launch: Option<&launch::Launch>,
+        linked_clones: &[String],
+    ) -> Element<crate::app::Message> {
+        let vm = match &self.vm {
+            Ok(vm) => vm,
+            Err(e) => {
+                let error = match e {
+                    EntryError::Missing => "Config file no longer exists".to_string(),
+                    EntryError::Unreadable(e) => format!("Could not read config: {e}"),
+                };
+                let details = widget::column()
+                    .push(widget::text::heading(self.path.display().to_string()))
+                    .push(widget::text(error).class(cosmic::style::Text::Accent))
+                    .width(Length::Fill);
+
+                let locate = widget::button::icon(icon::from_name("edit-find-symbolic"))
+                    .on_press(Message::LocateVM(self.path.clone()).into())
+                    .tooltip("Locate Moved VM");
+                let remove = widget::button::icon(icon::from_name("list-remove-symbolic"))
+                    .on_press(Message::RemoveEntry(self.path.clone()).into())
+                    .tooltip("Remove from Library");
+
+                return widget::row()
+                    .align_y(Alignment::Center)
+                    .push(details)
+                    .push(locate)
+                    .push(remove)
+                    .into();
+            }
+        };
+        let config = vm.config();
+
+        let cores = vm
+            .cpu_cores()
+            .map_or_else(|| "Default".to_string(), |cores| cores.to_string());
+        let ram = vm.ram().map_or_else(
+            || "Default".to_string(),
+            |ram| Size::from_bytes(ram).to_string(),
+        );
+        let dir = vm.dir();
+        // The release is only known for VMs created here
+        let os = [
+            self.metadata.release.as_deref(),
+            self.metadata.edition.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(config.guest.to_string(), |os, part| format!("{os} {part}"));
+
+        let vm_icon = widget::icon::from_name(
+            self.metadata
+                .icon
+                .as_deref()
+                .unwrap_or(metadata::DEFAULT_ICON),
+        )
+        .size(32)
+        .icon();
+        let tags = (!self.metadata.tags.is_empty())
+            .then(|| widget::text::caption(format!("Tags: {}", self.metadata.tags.join(", "))));
+        let notes = (!self.metadata.notes.is_empty())
+            .then(|| widget::text::caption(self.metadata.notes.as_str()));
+        let linked_clones_note = (!linked_clones.is_empty()).then(|| {
+            widget::text::caption(format!(
+                "Base of linked clones: {}",
+                linked_clones.join(", ")
+            ))
+            .class(cosmic::style::Text::Accent)
+        });
+
+        let details = widget::column()
+            .push(widget::text::heading(vm.name()))
+            .push(widget::text(format!("{os} • {}", config.machine.arch)))
+            .push(widget::text(format!("CPU Cores: {cores} • RAM: {ram}")))
+            .push(widget::text(dir.display().to_string()))
+            .push_maybe(tags)
+            .push_maybe(notes)
+            .push_maybe(linked_clones_note)
+            .push_maybe(launch.map(launch::Launch::view))
+            .width(Length::Fill);
+
+        // Booting, resizing or deleting a VM would break linked clones of it
+        let is_base = !linked_clones.is_empty();
+
+        let controls = launch
+            .and_then(|launch| launch.controls(&self.path))
+            .unwrap_or_else(|| {
+                widget::button::icon(icon::from_name("media-playback-start-symbolic"))
+                    .on_press_maybe(
+                        (!is_base && !launch.is_some_and(launch::Launch::is_active))
+                            .then(|| Message::StartVM(self.path.clone()).into()),
+                    )
+                    .tooltip("Launch VM")
+                    .into()
+            });
+
+        let edit = widget::button::icon(icon::from_name("document-edit-symbolic"))
+            .on_press_maybe(
+                (!launch.is_some_and(launch::Launch::is_active))
+                    .then(|| Message::EditVM(self.path.clone()).into()),
+            )
+            .tooltip("Edit VM");
+
+        let edit_metadata = widget::button::icon(icon::from_name("tag-symbolic"))
+            .on_press(Message::EditMetadata(self.path.clone()).into())
+            .tooltip("Notes and Tags");
+
+        let console = widget::button::icon(icon::from_name("utilities-terminal-symbolic"))
+            .on_press(Message::ShowConsole(self.path.clone()).into())
+            .tooltip("Serial Console");
+
+        let media = widget::button::icon(icon::from_name("media-optical-symbolic"))
+            .on_press(Message::EditMedia(self.path.clone()).into())
+            .tooltip("Media and Boot Order");
+
+        let port_forwards = widget::button::icon(icon::from_name("network-wired-symbolic"))
+            .on_press(Message::EditPortForwards(self.path.clone()).into())
+            .tooltip("Port Forwards");
+
+        let snapshots = widget::button::icon(icon::from_name("camera-photo-symbolic"))
+            .on_press(Message::ShowSnapshots(self.path.clone()).into())
+            .tooltip("Snapshots");
+
+        let resize = widget::button::icon(icon::from_name("zoom-fit-best-symbolic"))
+            .on_press_maybe(
+                (!is_base && !launch.is_some_and(launch::Launch::is_active))
+                    .then(|| Message::ResizeDisk(self.path.clone()).into()),
+            )
+            .tooltip("Resize Disk");
+
+        let clone = widget::button::icon(icon::from_name("edit-copy-symbolic"))
+            .on_press_maybe(
+                (!launch.is_some_and(launch::Launch::is_active))
+                    .then(|| Message::CloneVM(self.path.clone()).into()),
+            )
+            .tooltip("Clone VM");
+
+        let export = widget::button::icon(icon::from_name("document-export-symbolic"))
+            .on_press_maybe(
+                (!launch.is_some_and(launch::Launch::is_active))
+                    .then(|| Message::ExportVM(self.path.clone()).into()),
+            )
+            .tooltip("Export VM");
+
+        let delete = widget::button::icon(icon::from_name("user-trash-symbolic"))
+            .on_press_maybe(
+                (!is_base && !launch.is_some_and(launch::Launch::is_active))
+                    .then(|| Message::DeleteVM(self.path.clone()).into()),
+            )
+            .tooltip("Delete VM");
+
+        let open_dir = widget::button::icon(icon::from_name("folder-open-symbolic"))
+            .on_press(Message::OpenDir(dir).into())
+            .tooltip("Open VM Directory");
+
+        widget::row()
+            .align_y(Alignment::Center)
+            .spacing(theme::active().cosmic().spacing.space_xs)
+            .push(vm_icon)
+            .push(details)
+            .push(launch::badge(launch))
+            .push(controls)
+            .push(console)
+            .push(edit)
+            .push(edit_metadata)
+            .push(media)
+            .push(port_forwards)
+            .push(snapshots)
+            .push(resize)
+            .push(clone)
+            .push(export)
+            .push(delete)
+            .push(open_dir)
+            .into()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) enum Page {
+    #[default]
+    Loading,
+    List,
+    Console(PathBuf),
+    Edit(edit::EditVm),
+    Metadata(metadata::EditMetadata),
+    Media(media::Media),
+    PortForwards(network::PortForwards),
+    Snapshots(snapshots::Snapshots),
+    Resize(resize::ResizeDisk),
+    Usage(usage::StorageUsage),
+    Import(import::ImportVms),
+    ImportArchive(import_archive::ImportArchive),
+    Export(export::ExportVm),
+    Clone(clone::CloneVm),
+    Delete(delete::DeleteVm),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Refresh,
+    Loaded(Vec<Entry>),
+    LocateVM(PathBuf),
+    Located {
+        old_config_path: PathBuf,
+        new_config_path: PathBuf,
+    },
+    RemoveEntry(PathBuf),
+    StartVM(PathBuf),
+    Launch(PathBuf, launch::Event),
+    Control(PathBuf, launch::Action),
+    OpenDir(PathBuf),
+    ShowList,
+    FilterTag(Option<String>),
+    ShowConsole(PathBuf),
+    Console(PathBuf, console::Message),
+    /// Input for the serial console being shown.
+    ConsoleInput(Vec<u8>),
+    EditVM(PathBuf),
+    Edit(edit::Message),
+    EditMetadata(PathBuf),
+    Metadata(metadata::Message),
+    MetadataEdited {
+        config_path: PathBuf,
+        metadata: VmMetadata,
+    },
+    EditMedia(PathBuf),
+    Media(media::Message),
+    EditPortForwards(PathBuf),
+    PortForwards(network::Message),
+    ShowSnapshots(PathBuf),
+    Snapshots(snapshots::Message),
+    ResizeDisk(PathBuf),
+    Resize(resize::Message),
+    ShowUsage,
+    Usage(usage::Message),
+    ImportVMs,
+    Import(import::Message),
+    ShowImportArchive,
+    ImportArchive(import_archive::Message),
+    VMImported(PathBuf),
+    ExportVM(PathBuf),
+    Export(export::Message),
+    CloneVM(PathBuf),
+    Clone(clone::Message),
+    VMCloned(PathBuf),
+    DeleteVM(PathBuf),
+    Delete(delete::Message),
+    VMDeleted(PathBuf),
+    VMEdited {
+        old_config_path: PathBuf,
+        new_config_path: PathBuf,
+    },
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(config_path: &str, disks: &[&str], backing_files: &[&str]) -> Entry {
+        let path = PathBuf::from(config_path);
+        Entry {
+            vm: Ok(Vm::with_default_config(path.clone())),
+            metadata: VmMetadata::default(),
+            disks: disks.iter().map(PathBuf::from).collect(),
+            backing_files: backing_files.iter().map(PathBuf::from).collect(),
+            path,
+        }
+    }
+
+    #[test]
+    fn linked_clone_blocks_deleting_its_base() {
+        let base = entry("/vms/base.conf", &["/vms/base/disk.qcow2"], &[]);
+        let clone = entry(
+            "/vms/clone.conf",
+            &["/vms/clone/disk.qcow2"],
+            &["/vms/base/disk.qcow2"],
+        );
+        let base_vm = base.vm.clone().unwrap();
+        let (delete, _) = delete::DeleteVm::new(base_vm);
+        let mut state = State {
+            entries: vec![base, clone],
+            launches: HashMap::new(),
+            consoles: HashMap::new(),
+            tag_filter: None,
+            page: Page::Delete(delete),
+        };
+
+        assert_eq!(
+            linked_clones(&state.entries, Path::new("/vms/base.conf")),
+            ["clone"]
+        );
+        assert!(linked_clones(&state.entries, Path::new("/vms/clone.conf")).is_empty());
+
+        let _ = state.update(
+            Message::Delete(delete::Message::Delete(delete::Method::Permanent)),
+            &mut Config::default(),
+            None,
+        );
+        assert!(matches!(&state.page, Page::Delete(delete) if !delete.is_deleting()));
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget, Element,
+};
+
+use crate::{
+    creation::options::directory_row,
+    vm::{self, CloneMode, Vm},
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct CloneVm {
+    vm: Vm,
+    vm_name: String,
+    directory: PathBuf,
+    mode: CloneMode,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Cloning,
+    Failed(String),
+}
+
+impl CloneVm {
+    pub(super) fn new(vm: Vm) -> Self {
+        let directory = vm
+            .config_path()
+            .parent()
+            .map(ToOwned::to_owned)
+            .unwrap_or_default();
+        let vm_name = (1..)
+            .map(|n| match n {
+                1 => format!("{}-clone", vm.name()),
+                n => format!("{}-clone-{n}", vm.name()),
+            })
+            .find(|name| vm::is_valid_name(&directory, name))
+            .expect("A free name should always be found");
+        Self {
+            vm,
+            vm_name,
+            directory,
+            mode: CloneMode::Full,
+            status: Status::Idle,
+        }
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::SetName(vm_name) => self.vm_name = vm_name,
+            Message::SetMode(mode) => self.mode = mode,
+            Message::SelectVMDir => {
+                return Task::perform(crate::app::select_dir(), |dir| {
+                    match dir {
+                        Some(dir) => crate::app::Message::from(Message::SelectedVMDir(dir)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedVMDir(dir) => self.directory = dir,
+            Message::Clone => {
+                self.status = Status::Cloning;
+                let vm = self.vm.clone();
+                let directory = self.directory.clone();
+                let vm_name = self.vm_name.clone();
+                let mode = self.mode;
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || vm.clone_to(&directory, &vm_name, mode))
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    |result| {
+                        crate::app::Message::from(match result {
+                            Ok(clone) => super::Message::VMCloned(clone.config_path().to_owned()),
+                            Err(e) => Message::Error(e).into(),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self, running: bool) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Clone {}", self.vm.name())));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM before cloning it")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        let vm_name_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(widget::text("VM Name:  "))
+            .push(
+                widget::text_input("VM Name", self.vm_name.as_str())
+                    .on_input(|name| Message::SetName(name).into()),
+            );
+        list = list.add(vm_name_row);
+
+        list = list.add(directory_row(
+            &self.directory,
+            |dir| Message::SelectedVMDir(dir).into(),
+            Message::SelectVMDir.into(),
+        ));
+
+        let mode_row = widget::column()
+            .push(widget::radio(
+                "Full copy",
+                CloneMode::Full,
+                Some(self.mode),
+                |mode| Message::SetMode(mode).into(),
+            ))
+            .push(widget::radio(
+                "Linked clone (qcow2 backing file)",
+                CloneMode::Linked,
+                Some(self.mode),
+                |mode| Message::SetMode(mode).into(),
+            ));
+        list = list.add(mode_row);
+
+        if self.mode == CloneMode::Linked {
+            list = list.add(widget::text::caption(
+                "Linked clones depend on the original disk image, which must not be modified or \
+                 deleted while they exist",
+            ));
+        }
+
+        match &self.status {
+            Status::Cloning => list = list.add(widget::text("Cloning VM")),
+            Status::Failed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't clone VM: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Idle => {}
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let can_clone = !running
+                && self.status != Status::Cloning
+                && vm::is_valid_name(&self.directory, &self.vm_name);
+            let clone = widget::button::suggested("Clone")
+                .on_press_maybe(can_clone.then(|| Message::Clone.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(clone)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SetName(String),
+    SetMode(CloneMode),
+    SelectVMDir,
+    SelectedVMDir(PathBuf),
+    Clone,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Clone(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use cosmic::{
+    app::command::Task,
+    iced::{
+        clipboard,
+        keyboard::{key::Named, Key, Modifiers},
+        Alignment, Length, Subscription,
+    },
+    theme, widget, Apply, Element,
+};
+use futures_util::SinkExt;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+    sync::mpsc::{self, UnboundedSender},
+};
+
+use crate::{serial::Terminal, vm::Vm};
+
+/// How long to wait before retrying when the serial socket isn't available.
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+/// QEMU discards serial output while nothing is connected, so while it's starting up the socket
+/// is retried much more often.
+const STARTUP_RECONNECT_INTERVAL: Duration = Duration::from_millis(10);
+const STARTUP_DURATION: Duration = Duration::from_secs(10);
+/// Rendering the full scrollback on every frame is slow, so only the tail is displayed.
+const DISPLAYED_LINES: usize = 1000;
+
+/// The serial console of a VM. Output is captured for as long as the console is attached, which
+/// happens as soon as the app launches a VM so that boot messages aren't missed. Key presses are
+/// forwarded to the guest as they happen while the console is shown.
+#[derive(Debug, Clone)]
+pub(crate) struct Console {
+    config_path: PathBuf,
+    vm_name: String,
+    socket: PathBuf,
+    terminal: Terminal,
+    attached: bool,
+    /// Bumped for every boot, so that a fresh connection is started right away rather than
+    /// waiting for the previous one to notice that QEMU went away.
+    generation: u64,
+    input: Option<UnboundedSender<Vec<u8>>>,
+}
+
+impl Console {
+    pub(super) fn new(vm: &Vm) -> Self {
+        Self {
+            config_path: vm.config_path().to_owned(),
+            vm_name: vm.name().to_string(),
+            socket: vm.serial_socket(),
+            terminal: Terminal::default(),
+            attached: false,
+            generation: 0,
+            input: None,
+        }
+    }
+
+    pub(super) fn attach(&mut self) {
+        self.attached = true;
+    }
+
+    pub(super) fn detach(&mut self) {
+        self.attached = false;
+        self.input = None;
+    }
+
+    /// Starts capturing afresh, for a new boot of the VM.
+    pub(super) fn reset(&mut self) {
+        self.terminal.clear();
+        self.generation += 1;
+        self.input = None;
+        self.attach();
+    }
+
+    pub(super) fn send(&self, bytes: Vec<u8>) {
+        if let Some(input) = &self.input {
+            _ = input.send(bytes);
+        }
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Event(Event::Connected(input)) => self.input = Some(input),
+            Message::Event(Event::Output(bytes)) => self.terminal.feed(&bytes),
+            Message::Event(Event::Disconnected) => self.input = None,
+            Message::Paste => {
+                let config_path = self.config_path.clone();
+                return clipboard::read().map(move |text| {
+                    crate::app::Message::from(super::Message::Console(
+                        config_path.clone(),
+                        Message::Pasted(text),
+                    ))
+                    .into()
+                });
+            }
+            Message::Pasted(text) => {
+                if let Some(text) = text {
+                    self.send(text.replace('\n', "\r").into_bytes());
+                }
+            }
+            Message::CopyOutput => return clipboard::write(self.terminal.text()),
+            Message::Clear => self.terminal.clear(),
+            Message::Attach => self.attach(),
+            Message::Detach => self.detach(),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let message = |msg: Message| {
+            crate::app::Message::from(super::Message::Console(self.config_path.clone(), msg))
+        };
+        let connected = self.input.is_some();
+
+        let mut list = widget::list_column();
+
+        let (status, attach) = match (self.attached, connected) {
+            (true, true) => ("Connected", None),
+            (true, false) => (
+                "Waiting for the VM's serial port",
+                Some(widget::button::standard("Detach").on_press(message(Message::Detach))),
+            ),
+            (false, _) => (
+                "Detached",
+                Some(widget::button::standard("Attach").on_press(message(Message::Attach))),
+            ),
+        };
+        list = list.add(
+            widget::row()
+                .align_y(Alignment::Center)
+                .push(
+                    widget::column()
+                        .push(widget::text::title4(format!(
+                            "Serial Console of {}",
+                            self.vm_name
+                        )))
+                        .push(widget::text::caption(status))
+                        .width(Length::Fill),
+                )
+                .push_maybe(attach)
+                .push(
+                    widget::button::standard("Paste")
+                        .on_press_maybe(connected.then(|| message(Message::Paste))),
+                )
+                .push(widget::button::standard("Copy Output").on_press_maybe(
+                    (!self.terminal.is_empty()).then(|| message(Message::CopyOutput)),
+                ))
+                .push(widget::button::standard("Clear").on_press(message(Message::Clear))),
+        );
+
+        let output = if self.terminal.is_empty() {
+            widget::text("No output has been captured yet")
+        } else {
+            widget::text(self.terminal.tail(DISPLAYED_LINES))
+        }
+        .font(cosmic::font::mono())
+        .width(Length::Fill);
+        list = list.add(
+            widget::list_column()
+                .style(theme::Container::ContextDrawer)
+                .add(widget::scrollable(output).anchor_bottom())
+                .apply(widget::container)
+                .height(Length::Fill),
+        );
+
+        list = list.add(widget::text::caption(
+            "Keys pressed while this page is open are sent to the VM",
+        ));
+
+        list =
+            list.add(widget::button::suggested("Back").on_press(super::Message::ShowList.into()));
+
+        list.into()
+    }
+}
+
+/// Keeps a connection open to the serial socket of every attached console.
+pub(super) fn subscription<'a>(
+    consoles: impl Iterator<Item = (&'a PathBuf, &'a Console)>,
+) -> Subscription<crate::app::Message> {
+    struct SerialConnection;
+
+    Subscription::batch(consoles.filter(|(_, console)| console.attached).map(
+        |(config_path, console)| {
+            Subscription::run_with_id(
+                (
+                    std::any::TypeId::of::<SerialConnection>(),
+                    config_path.clone(),
+                    console.generation,
+                ),
+                connect(config_path.clone(), console.socket.clone()),
+            )
+        },
+    ))
+}
+
+fn connect(
+    config_path: PathBuf,
+    socket: PathBuf,
+) -> impl futures_util::Stream<Item = crate::app::Message> {
+    cosmic::iced::stream::channel(64, move |mut output| async move {
+        let message = |event| {
+            crate::app::Message::from(super::Message::Console(
+                config_path.clone(),
+                Message::Event(event),
+            ))
+        };
+
+        let started = Instant::now();
+        loop {
+            let Ok(stream) = UnixStream::connect(&socket).await else {
+                let interval = if started.elapsed() < STARTUP_DURATION {
+                    STARTUP_RECONNECT_INTERVAL
+                } else {
+                    RECONNECT_INTERVAL
+                };
+                tokio::time::sleep(interval).await;
+                continue;
+            };
+            let (mut reader, mut writer) = stream.into_split();
+            let (input, mut input_rx) = mpsc::unbounded_channel();
+            _ = output.send(message(Event::Connected(input))).await;
+
+            let mut buf = vec![0; 4096];
+            loop {
+                tokio::select! {
+                    read = reader.read(&mut buf) => match read {
+                        Ok(0) | Err(_) => break,
+                        Ok(len) => {
+                            _ = output.send(message(Event::Output(buf[..len].to_vec()))).await;
+                        }
+                    },
+                    Some(bytes) = input_rx.recv() => {
+                        if writer.write_all(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            _ = output.send(message(Event::Disconnected)).await;
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+        }
+    })
+}
+
+/// Translates a key press into the bytes a terminal would send for it.
+pub(super) fn key_input(key: Key, modifiers: Modifiers) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match key {
+        Key::Character(c) if modifiers.control() => {
+            // Control characters, such as ETX for Ctrl+C
+            return match c.as_bytes() {
+                [c @ (b'@'..=b'_' | b'a'..=b'z')] => Some(vec![c.to_ascii_uppercase() & 0x1f]),
+                _ => None,
+            };
+        }
+        Key::Character(_) if modifiers.alt() || modifiers.logo() => return None,
+        Key::Character(c) => return Some(c.as_bytes().to_vec()),
+        Key::Named(Named::Enter) => b"\r",
+        Key::Named(Named::Space) => b" ",
+        Key::Named(Named::Tab) => b"\t",
+        Key::Named(Named::Backspace) => b"\x7f",
+        Key::Named(Named::Escape) => b"\x1b",
+        Key::Named(Named::ArrowUp) => b"\x1b[A",
+        Key::Named(Named::ArrowDown) => b"\x1b[B",
+        Key::Named(Named::ArrowRight) => b"\x1b[C",
+        Key::Named(Named::ArrowLeft) => b"\x1b[D",
+        Key::Named(Named::Home) => b"\x1b[H",
+        Key::Named(Named::End) => b"\x1b[F",
+        Key::Named(Named::Insert) => b"\x1b[2~",
+        Key::Named(Named::Delete) => b"\x1b[3~",
+        Key::Named(Named::PageUp) => b"\x1b[5~",
+        Key::Named(Named::PageDown) => b"\x1b[6~",
+        _ => return None,
+    };
+    Some(bytes.to_vec())
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    /// The serial socket was connected to, giving a channel for sending input to the guest.
+    Connected(UnboundedSender<Vec<u8>>),
+    Output(Vec<u8>),
+    Disconnected,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Event(Event),
+    Paste,
+    Pasted(Option<String>),
+    CopyOutput,
+    Clear,
+    Attach,
+    Detach,
+}
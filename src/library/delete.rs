@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme,
+    widget::{self, icon},
+    Element,
+};
+use size::Size;
+
+use crate::vm::{Vm, VmFile, VmFileKind};
+
+#[derive(Debug, Clone)]
+pub(crate) struct DeleteVm {
+    vm: Vm,
+    files: Option<Vec<VmFile>>,
+    keep_media: bool,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Deleting,
+    /// Moving files to the trash failed; the user may choose to delete them permanently instead.
+    TrashFailed(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Method {
+    Trash,
+    Permanent,
+}
+
+impl DeleteVm {
+    pub(super) fn new(vm: Vm) -> (Self, Task<crate::app::Message>) {
+        let scan_vm = vm.clone();
+        let task = Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || scan_vm.files())
+                    .await
+                    .expect("Couldn't spawn thread")
+            },
+            |files| {
+                crate::app::Message::from(match files {
+                    Ok(files) => Message::Scanned(files),
+                    Err(e) => Message::Error(format!("Couldn't list VM files: {e}")),
+                })
+                .into()
+            },
+        );
+        (
+            Self {
+                vm,
+                files: None,
+                keep_media: false,
+                status: Status::Idle,
+            },
+            task,
+        )
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    #[cfg(test)]
+    pub(super) fn is_deleting(&self) -> bool {
+        self.status == Status::Deleting
+    }
+
+    fn files_to_delete(&self) -> impl Iterator<Item = &VmFile> {
+        self.files
+            .iter()
+            .flatten()
+            .filter(|file| !(self.keep_media && file.kind == VmFileKind::InstallerMedia))
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Scanned(files) => self.files = Some(files),
+            Message::SetKeepMedia(keep_media) => self.keep_media = keep_media,
+            Message::Delete(method) => {
+                self.status = Status::Deleting;
+                let paths: Vec<PathBuf> = self
+                    .files_to_delete()
+                    .map(|file| file.path.clone())
+                    .collect();
+                let vm_dir = self.vm.dir();
+                let config_path = self.vm.config_path().to_owned();
+
+                return Task::perform(
+                    async move {
+                        for path in &paths {
+                            // Files may already be gone after a partially successful attempt
+                            if !path.exists() {
+                                continue;
+                            }
+                            match method {
+                                Method::Trash => trash(path).await?,
+                                Method::Permanent => tokio::fs::remove_file(path)
+                                    .await
+                                    .map_err(|e| format!("{}: {e}", path.display()))?,
+                            }
+                        }
+                        // Directories left empty are removed; any kept media keeps its directory
+                        tokio::task::spawn_blocking(move || remove_empty_dirs(&vm_dir))
+                            .await
+                            .expect("Couldn't spawn thread");
+                        Ok(())
+                    },
+                    move |result: Result<(), String>| {
+                        crate::app::Message::from(match result {
+                            Ok(()) => super::Message::VMDeleted(config_path),
+                            Err(e) => match method {
+                                Method::Trash => Message::TrashFailed(e).into(),
+                                Method::Permanent => Message::Error(e).into(),
+                            },
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::TrashFailed(e) => self.status = Status::TrashFailed(e),
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(
+        &self,
+        running: bool,
+        linked_clones: &[String],
+    ) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Delete {}?", self.vm.name())));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM before deleting it")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+        if !linked_clones.is_empty() {
+            list = list.add(
+                widget::text(format!(
+                    "Linked clones depend on this VM's disks. Delete them first: {}",
+                    linked_clones.join(", ")
+                ))
+                .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        match &self.files {
+            None => list = list.add(widget::text("Finding VM files")),
+            Some(_) => {
+                let files = self.files_to_delete().fold(
+                    widget::list_column().style(theme::Container::ContextDrawer),
+                    |files, file| {
+                        files.add(
+                            widget::row()
+                                .align_y(Alignment::Center)
+                                .push(
+                                    widget::text(file.path.display().to_string())
+                                        .width(Length::Fill),
+                                )
+                                .push(widget::text::caption(format!(
+                                    "{} • {}",
+                                    file.kind,
+                                    Size::from_bytes(file.size)
+                                ))),
+                        )
+                    },
+                );
+                let total_size: u64 = self.files_to_delete().map(|file| file.size).sum();
+
+                list = list
+                    .add(widget::text(format!(
+                        "The following files will be removed ({}):",
+                        Size::from_bytes(total_size)
+                    )))
+                    .add(widget::scrollable(files).height(Length::Fill))
+                    .add(
+                        widget::checkbox("Keep downloaded installer media", self.keep_media)
+                            .on_toggle(|keep| Message::SetKeepMedia(keep).into()),
+                    );
+            }
+        }
+
+        match &self.status {
+            Status::TrashFailed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't move files to the trash: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Failed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't delete VM: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Idle | Status::Deleting => {}
+        }
+
+        let nav_row = {
+            let can_delete = !running
+                && linked_clones.is_empty()
+                && self.files.is_some()
+                && self.status != Status::Deleting;
+
+            let cancel =
+                widget::button::suggested("Cancel").on_press(super::Message::ShowList.into());
+
+            let delete = if matches!(self.status, Status::TrashFailed(_)) {
+                widget::button::destructive("Delete Permanently")
+                    .on_press_maybe(can_delete.then(|| Message::Delete(Method::Permanent).into()))
+            } else {
+                widget::button::destructive("Move to Trash")
+                    .leading_icon(icon::from_name("user-trash-symbolic"))
+                    .on_press_maybe(can_delete.then(|| Message::Delete(Method::Trash).into()))
+            };
+
+            widget::row().align_y(Alignment::Center).push(cancel).push(
+                widget::container(delete)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+async fn trash(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    ashpd::desktop::trash::trash_file(&file)
+        .await
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn remove_empty_dirs(dir: &Path) {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                remove_empty_dirs(&entry.path());
+            }
+        }
+    }
+    // Fails if the directory still holds files, which is intended
+    _ = std::fs::remove_dir(dir);
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Scanned(Vec<VmFile>),
+    SetKeepMedia(bool),
+    Delete(Method),
+    TrashFailed(String),
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Delete(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget, Element,
+};
+
+use crate::{
+    creation::options::{
+        cpu_cores_row, directory_row, ram_row, shared_dir_row, RECOMMENDED_CPU_CORES,
+        RECOMMENDED_RAM,
+    },
+    vm::Vm,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct EditVm {
+    vm: Vm,
+    cpu_cores: usize,
+    ram: f64,
+    directory: PathBuf,
+    shared_dir: Option<PathBuf>,
+    error: Option<String>,
+}
+
+impl EditVm {
+    pub(super) fn new(vm: Vm) -> Self {
+        let directory = vm
+            .config_path()
+            .parent()
+            .map(ToOwned::to_owned)
+            .unwrap_or_default();
+        Self {
+            cpu_cores: vm.cpu_cores().unwrap_or(*RECOMMENDED_CPU_CORES),
+            ram: vm.ram().map_or(*RECOMMENDED_RAM, |ram| ram as f64),
+            directory,
+            shared_dir: vm.shared_dir().map(ToOwned::to_owned),
+            error: None,
+            vm,
+        }
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    /// Whether saving moves the VM to another directory.
+    pub(super) fn moves(&self) -> bool {
+        self.vm.config_path().parent() != Some(self.directory.as_path())
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::SetCPUCores(cores) => self.cpu_cores = cores,
+            Message::SetRAM(ram) => self.ram = ram,
+            Message::SelectVMDir => {
+                return Task::perform(crate::app::select_dir(), |dir| {
+                    match dir {
+                        Some(dir) => crate::app::Message::from(Message::SelectedVMDir(dir)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedVMDir(dir) => self.directory = dir,
+            Message::SelectSharedDir => {
+                return Task::perform(crate::app::select_dir(), |dir| {
+                    match dir {
+                        Some(dir) => crate::app::Message::from(Message::SelectedSharedDir(dir)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedSharedDir(dir) => self.shared_dir = Some(dir),
+            Message::ClearSharedDir => self.shared_dir = None,
+            Message::Save => {
+                let mut vm = self.vm.clone();
+                vm.set_cpu_cores(self.cpu_cores);
+                vm.set_ram(self.ram as u64);
+                vm.set_shared_dir(self.shared_dir.clone());
+                let directory = self.directory.clone();
+                let old_config_path = vm.config_path().to_owned();
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            if vm.config_path().parent() != Some(directory.as_path()) {
+                                vm.relocate(&directory)?;
+                            } else {
+                                vm.save()?;
+                            }
+                            Ok(vm.config_path().to_owned())
+                        })
+                        .await
+                        .expect("Couldn't spawn thread")
+                    },
+                    move |result| {
+                        crate::app::Message::from(match result {
+                            Ok(new_config_path) => super::Message::VMEdited {
+                                old_config_path,
+                                new_config_path,
+                            },
+                            Err(e) => Message::Error(e).into(),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Error(e) => self.error = Some(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(
+        &self,
+        running: bool,
+        linked_clones: &[String],
+    ) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Edit {}", self.vm.name())));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM to edit its hardware")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        list = list.add(cpu_cores_row(self.cpu_cores, |cores| {
+            Message::SetCPUCores(cores).into()
+        }));
+        list = list.add(ram_row(self.ram, |ram| Message::SetRAM(ram).into()));
+        list = list.add(directory_row(
+            &self.directory,
+            |dir| Message::SelectedVMDir(dir).into(),
+            Message::SelectVMDir.into(),
+        ));
+        list = list.add(shared_dir_row(
+            self.shared_dir.as_deref(),
+            self.vm.shared_dir_mount_command(),
+            Message::SelectSharedDir.into(),
+            Message::ClearSharedDir.into(),
+        ));
+
+        // Linked clones refer to the base images by path
+        let blocks_move = self.moves() && !linked_clones.is_empty();
+        if blocks_move {
+            list = list.add(
+                widget::text(format!(
+                    "Linked clones depend on this VM's disks, so it can't be moved: {}",
+                    linked_clones.join(", ")
+                ))
+                .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        if let Some(error) = &self.error {
+            list = list.add(widget::text(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let save = widget::button::suggested("Save").on_press_maybe(
+                (!running && !blocks_move && self.directory.is_dir()).then(|| Message::Save.into()),
+            );
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(save)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SetCPUCores(usize),
+    SetRAM(f64),
+    SelectVMDir,
+    SelectedVMDir(PathBuf),
+    SelectSharedDir,
+    SelectedSharedDir(PathBuf),
+    ClearSharedDir,
+    Save,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Edit(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
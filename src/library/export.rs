@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget::{self, icon},
+    Element,
+};
+use size::Size;
+
+use crate::{
+    archive,
+    vm::{Vm, VmFileKind},
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ExportVm {
+    vm: Vm,
+    directory: PathBuf,
+    include_installer_media: bool,
+    installer_media_size: Option<u64>,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Exporting,
+    Exported,
+    Failed(String),
+}
+
+impl ExportVm {
+    pub(super) fn new(vm: Vm) -> (Self, Task<crate::app::Message>) {
+        let directory = vm
+            .config_path()
+            .parent()
+            .map(ToOwned::to_owned)
+            .unwrap_or_default();
+        let scan_vm = vm.clone();
+        let task = Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    scan_vm.files().ok().map(|files| {
+                        files
+                            .iter()
+                            .filter(|file| file.kind == VmFileKind::InstallerMedia)
+                            .map(|file| file.size)
+                            .sum::<u64>()
+                    })
+                })
+                .await
+                .expect("Couldn't spawn thread")
+            },
+            |size| crate::app::Message::from(Message::Scanned(size)).into(),
+        );
+        (
+            Self {
+                vm,
+                directory,
+                include_installer_media: false,
+                installer_media_size: None,
+                status: Status::Idle,
+            },
+            task,
+        )
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    fn destination(&self) -> PathBuf {
+        self.directory.join(format!("{}.tar.gz", self.vm.name()))
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Scanned(size) => self.installer_media_size = size,
+            Message::SetIncludeInstallerMedia(include) => self.include_installer_media = include,
+            Message::SelectDir => {
+                return Task::perform(crate::app::select_dir(), |dir| {
+                    match dir {
+                        Some(dir) => crate::app::Message::from(Message::SelectedDir(dir)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedDir(dir) => {
+                self.directory = dir;
+                self.status = Status::Idle;
+            }
+            Message::Export => {
+                self.status = Status::Exporting;
+                let vm = self.vm.clone();
+                let destination = self.destination();
+                let include_installer_media = self.include_installer_media;
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            archive::export(&vm, &destination, include_installer_media)
+                        })
+                        .await
+                        .expect("Couldn't spawn thread")
+                    },
+                    |result| {
+                        crate::app::Message::from(match result {
+                            Ok(()) => Message::Exported,
+                            Err(e) => Message::Error(e),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Exported => self.status = Status::Exported,
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self, running: bool) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Export {}", self.vm.name())));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM before exporting it")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        let dir_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(widget::text("Export To:  "))
+            .push(
+                widget::text_input("Export Directory", self.directory.display().to_string())
+                    .on_input(|dir| Message::SelectedDir(PathBuf::from(dir)).into()),
+            )
+            .push(
+                widget::button::icon(icon::from_name("folder-open-symbolic"))
+                    .on_press(Message::SelectDir.into())
+                    .tooltip("Select Export Directory"),
+            );
+        list = list.add(dir_row);
+        list = list.add(widget::text::caption(
+            self.destination().display().to_string(),
+        ));
+
+        let media_label = match self.installer_media_size {
+            Some(size) => format!("Include installer media ({})", Size::from_bytes(size)),
+            None => "Include installer media".to_string(),
+        };
+        list = list.add(
+            widget::checkbox(media_label, self.include_installer_media)
+                .on_toggle(|include| Message::SetIncludeInstallerMedia(include).into()),
+        );
+
+        match &self.status {
+            Status::Exporting => list = list.add(widget::text("Exporting VM")),
+            Status::Exported => {
+                list = list.add(
+                    widget::row()
+                        .align_y(Alignment::Center)
+                        .push(
+                            widget::text(format!("Exported to {}", self.destination().display()))
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            widget::button::standard("Open Directory")
+                                .on_press(super::Message::OpenDir(self.directory.clone()).into()),
+                        ),
+                )
+            }
+            Status::Failed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't export VM: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Idle => {}
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let can_export = !running
+                && self.status != Status::Exporting
+                && self.directory.is_dir()
+                && !self.destination().exists();
+            let export = widget::button::suggested("Export")
+                .on_press_maybe(can_export.then(|| Message::Export.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(export)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Scanned(Option<u64>),
+    SetIncludeInstallerMedia(bool),
+    SelectDir,
+    SelectedDir(PathBuf),
+    Export,
+    Exported,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Export(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
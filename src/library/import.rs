@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme, widget, Element,
+};
+
+use crate::vm::Vm;
+
+/// Configs nested deeper than this below the chosen directory aren't searched for.
+const MAX_SCAN_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub(crate) struct ImportVms {
+    directory: Option<PathBuf>,
+    status: Status,
+}
+
+#[derive(Debug, Clone)]
+enum Status {
+    SelectingDir,
+    Scanning,
+    Found {
+        vms: Vec<(Vm, bool)>,
+        already_registered: usize,
+    },
+}
+
+impl ImportVms {
+    pub(super) fn new() -> (Self, Task<crate::app::Message>) {
+        (
+            Self {
+                directory: None,
+                status: Status::SelectingDir,
+            },
+            select_dir(),
+        )
+    }
+
+    pub(super) fn update(
+        &mut self,
+        msg: Message,
+        registered: &[PathBuf],
+    ) -> Task<crate::app::Message> {
+        match msg {
+            Message::SelectDir => {
+                self.status = Status::SelectingDir;
+                return select_dir();
+            }
+            Message::SelectedDir(dir) => {
+                self.directory = Some(dir.clone());
+                self.status = Status::Scanning;
+                let registered = registered.to_vec();
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let mut vms = vec![];
+                            find_configs(&dir, 0, &mut vms);
+                            let (already_registered, vms): (Vec<_>, Vec<_>) = vms
+                                .into_iter()
+                                .partition(|vm| is_registered(&registered, vm.config_path()));
+                            (vms, already_registered.len())
+                        })
+                        .await
+                        .expect("Couldn't spawn thread")
+                    },
+                    |(vms, already_registered)| {
+                        crate::app::Message::from(Message::Scanned(vms, already_registered)).into()
+                    },
+                );
+            }
+            Message::Scanned(vms, already_registered) => {
+                self.status = Status::Found {
+                    vms: vms.into_iter().map(|vm| (vm, true)).collect(),
+                    already_registered,
+                };
+            }
+            Message::Toggle(index, selected) => {
+                if let Status::Found { ref mut vms, .. } = self.status {
+                    if let Some((_, vm_selected)) = vms.get_mut(index) {
+                        *vm_selected = selected;
+                    }
+                }
+            }
+            // Registering the selected VMs is handled by the library
+            Message::Import => {}
+        }
+        Task::none()
+    }
+
+    pub(super) fn selected_configs(&self) -> Vec<PathBuf> {
+        match self.status {
+            Status::Found { ref vms, .. } => vms
+                .iter()
+                .filter(|(_, selected)| *selected)
+                .map(|(vm, _)| vm.config_path().to_owned())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4("Import Existing VMs"));
+
+        let dir_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(widget::text(
+                self.directory
+                    .as_ref()
+                    .map_or_else(String::new, |dir| dir.display().to_string()),
+            ))
+            .push(widget::horizontal_space())
+            .push(widget::button::standard("Choose Directory").on_press(Message::SelectDir.into()));
+        list = list.add(dir_row);
+
+        match &self.status {
+            Status::SelectingDir => {
+                list = list.add(widget::text(
+                    "Choose a directory to search for quickemu configs",
+                ))
+            }
+            Status::Scanning => list = list.add(widget::text("Searching for VMs")),
+            Status::Found {
+                vms,
+                already_registered,
+            } => {
+                let summary = match (vms.len(), already_registered) {
+                    (0, 0) => "No VMs were found".to_string(),
+                    (found, 0) => format!("Found {found} VMs"),
+                    (found, skipped) => {
+                        format!("Found {found} VMs ({skipped} already in the library were skipped)")
+                    }
+                };
+                list = list.add(widget::text(summary));
+
+                let vm_list = vms.iter().enumerate().fold(
+                    widget::list_column().style(theme::Container::ContextDrawer),
+                    |vm_list, (index, (vm, selected))| {
+                        let details = widget::column()
+                            .push(widget::text::heading(vm.name()))
+                            .push(widget::text::caption(
+                                vm.config_path().display().to_string(),
+                            ));
+                        vm_list.add(
+                            widget::row()
+                                .align_y(Alignment::Center)
+                                .push(widget::checkbox("", *selected).on_toggle(move |selected| {
+                                    Message::Toggle(index, selected).into()
+                                }))
+                                .push(details),
+                        )
+                    },
+                );
+                list = list.add(widget::scrollable(vm_list).height(Length::Fill));
+            }
+        }
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let can_import = matches!(
+                &self.status,
+                Status::Found { vms, .. } if vms.iter().any(|(_, selected)| *selected)
+            );
+            let import = widget::button::suggested("Import")
+                .on_press_maybe(can_import.then(|| Message::Import.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(import)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+fn select_dir() -> Task<crate::app::Message> {
+    Task::perform(crate::app::select_dir(), |dir| {
+        match dir {
+            Some(dir) => crate::app::Message::from(Message::SelectedDir(dir)),
+            _ => crate::app::Message::None,
+        }
+        .into()
+    })
+}
+
+fn is_registered(registered: &[PathBuf], config_path: &Path) -> bool {
+    let canonical = config_path.canonicalize().ok();
+    registered.iter().any(|path| {
+        path == config_path || (canonical.is_some() && path.canonicalize().ok() == canonical)
+    })
+}
+
+/// Recursively searches a directory for files which parse as quickemu configs.
+fn find_configs(dir: &Path, depth: usize, vms: &mut Vec<Vm>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() && !hidden && depth < MAX_SCAN_DEPTH {
+            find_configs(&path, depth + 1, vms);
+        } else if file_type.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext == "conf" || ext == "toml")
+        {
+            if let Ok(vm) = Vm::load(path) {
+                vms.push(vm);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SelectDir,
+    SelectedDir(PathBuf),
+    Scanned(Vec<Vm>, usize),
+    Toggle(usize, bool),
+    Import,
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Import(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget, Element,
+};
+
+use crate::archive::{self, Manifest};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ImportArchive {
+    default_vm_dir: PathBuf,
+    archive: Option<PathBuf>,
+    manifest: Option<Manifest>,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Reading,
+    Importing,
+    Failed(String),
+}
+
+impl ImportArchive {
+    pub(super) fn new(default_vm_dir: PathBuf) -> (Self, Task<crate::app::Message>) {
+        (
+            Self {
+                default_vm_dir,
+                archive: None,
+                manifest: None,
+                status: Status::Idle,
+            },
+            select_archive(),
+        )
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::SelectArchive => return select_archive(),
+            Message::SelectedArchive(path) => {
+                self.archive = Some(path.clone());
+                self.manifest = None;
+                self.status = Status::Reading;
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || archive::read_manifest(&path))
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    |result| {
+                        crate::app::Message::from(match result {
+                            Ok(manifest) => Message::Read(manifest),
+                            Err(e) => Message::Error(e),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Read(manifest) => {
+                self.manifest = Some(manifest);
+                self.status = Status::Idle;
+            }
+            Message::Import => {
+                let Some(path) = self.archive.clone() else {
+                    return Task::none();
+                };
+                self.status = Status::Importing;
+                let default_vm_dir = self.default_vm_dir.clone();
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || archive::import(&path, &default_vm_dir))
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    |result| {
+                        crate::app::Message::from(match result {
+                            Ok(vm) => super::Message::VMImported(vm.config_path().to_owned()),
+                            Err(e) => Message::Error(e).into(),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4("Import VM Archive"));
+
+        let archive_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(widget::text(
+                self.archive
+                    .as_ref()
+                    .map_or_else(String::new, |path| path.display().to_string()),
+            ))
+            .push(widget::horizontal_space())
+            .push(
+                widget::button::standard("Choose Archive").on_press(Message::SelectArchive.into()),
+            );
+        list = list.add(archive_row);
+
+        if let Some(manifest) = &self.manifest {
+            let exported = chrono::DateTime::parse_from_rfc3339(&manifest.exported)
+                .map(|date| {
+                    date.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_else(|_| manifest.exported.clone());
+            let media = if manifest.includes_installer_media {
+                "Includes installer media"
+            } else {
+                "Installer media not included"
+            };
+            list = list
+                .add(widget::text::heading(manifest.vm_name.as_str()))
+                .add(widget::text(format!(
+                    "{} • Exported {exported} • {media}",
+                    manifest.guest
+                )))
+                .add(widget::text(format!(
+                    "The VM will be unpacked into {}",
+                    self.default_vm_dir.join(&manifest.dir).display()
+                )));
+        }
+
+        match &self.status {
+            Status::Reading => list = list.add(widget::text("Reading archive")),
+            Status::Importing => list = list.add(widget::text("Unpacking VM")),
+            Status::Failed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't import archive: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Idle => {}
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let can_import = self.manifest.is_some()
+                && !matches!(self.status, Status::Reading | Status::Importing);
+            let import = widget::button::suggested("Import")
+                .on_press_maybe(can_import.then(|| Message::Import.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(import)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+fn select_archive() -> Task<crate::app::Message> {
+    Task::perform(crate::app::select_file("Select VM Archive"), |path| {
+        match path {
+            Some(path) => crate::app::Message::from(Message::SelectedArchive(path)),
+            _ => crate::app::Message::None,
+        }
+        .into()
+    })
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SelectArchive,
+    SelectedArchive(PathBuf),
+    Read(Manifest),
+    Import,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::ImportArchive(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
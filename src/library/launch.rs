@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Subscription},
+    widget::{self, icon},
+    Element,
+};
+use futures_util::SinkExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::{qmp::Qmp, vm::Vm};
+
+/// Only the tail of QEMU's stderr is kept around for display.
+const MAX_STDERR_LINES: usize = 200;
+const DISPLAYED_STDERR_LINES: usize = 5;
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A VM launched by the app, tracked for as long as its QEMU process lives.
+#[derive(Debug, Clone)]
+pub(crate) struct Launch {
+    status: Status,
+    stderr: Vec<String>,
+    control_error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Status {
+    Starting,
+    Running(RunningVm),
+    Exited(Option<i32>),
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+struct RunningVm {
+    pid: Option<u32>,
+    started: Instant,
+    qmp_socket: PathBuf,
+    /// The run state last reported by QEMU, e.g. "running" or "paused".
+    qemu_status: Option<String>,
+}
+
+impl Launch {
+    pub(super) fn start(config_path: PathBuf) -> (Self, Task<crate::app::Message>) {
+        let stream = cosmic::iced::stream::channel(16, move |mut output| async move {
+            let load_path = config_path.clone();
+            let spawned = tokio::task::spawn_blocking(move || Vm::load(load_path))
+                .await
+                .expect("Couldn't spawn thread")
+                .and_then(|vm| Ok((vm.spawn()?, vm.qmp_socket())));
+            let (mut child, qmp_socket) = match spawned {
+                Ok(spawned) => spawned,
+                Err(e) => {
+                    _ = output.send((config_path, Event::Failed(e))).await;
+                    return;
+                }
+            };
+
+            let started = Event::Started {
+                pid: child.id(),
+                qmp_socket,
+            };
+            _ = output.send((config_path.clone(), started)).await;
+
+            if let Some(stderr) = child.stderr.take() {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    _ = output
+                        .send((config_path.clone(), Event::Stderr(line)))
+                        .await;
+                }
+            }
+
+            let event = match child.wait().await {
+                Ok(status) => Event::Exited(status.code()),
+                Err(e) => Event::Failed(format!("Error while waiting for QEMU: {e}")),
+            };
+            _ = output.send((config_path, event)).await;
+        });
+
+        let task = Task::run(stream, |(path, event)| {
+            crate::app::Message::from(super::Message::Launch(path, event)).into()
+        });
+
+        (
+            Self {
+                status: Status::Starting,
+                stderr: vec![],
+                control_error: None,
+            },
+            task,
+        )
+    }
+
+    pub(super) fn update(&mut self, event: Event) {
+        match event {
+            Event::Started { pid, qmp_socket } => {
+                self.status = Status::Running(RunningVm {
+                    pid,
+                    started: Instant::now(),
+                    qmp_socket,
+                    qemu_status: None,
+                })
+            }
+            Event::Stderr(line) => {
+                if self.stderr.len() >= MAX_STDERR_LINES {
+                    self.stderr.remove(0);
+                }
+                self.stderr.push(line);
+            }
+            Event::QemuStatus(status) => {
+                if let Status::Running(ref mut running) = self.status {
+                    running.qemu_status = Some(status);
+                }
+            }
+            Event::ControlResult(result) => self.control_error = result.err(),
+            Event::Exited(code) => self.status = Status::Exited(code),
+            Event::Failed(e) => self.status = Status::Failed(e),
+        }
+    }
+
+    pub(super) fn is_active(&self) -> bool {
+        matches!(self.status, Status::Starting | Status::Running(_))
+    }
+
+    pub(super) fn qmp_socket(&self) -> Option<&PathBuf> {
+        match self.status {
+            Status::Running(ref running) => Some(&running.qmp_socket),
+            _ => None,
+        }
+    }
+
+    /// Sends a lifecycle command to the VM over its QMP socket.
+    pub(super) fn control(
+        &self,
+        config_path: PathBuf,
+        action: Action,
+    ) -> Task<crate::app::Message> {
+        let Some(qmp_socket) = self.qmp_socket().cloned() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                let mut qmp = Qmp::connect(&qmp_socket).await?;
+                qmp.execute(action.qmp_command(), None).await
+            },
+            move |result| {
+                let result = result.map(|_| ()).map_err(|e| e.to_string());
+                crate::app::Message::from(super::Message::Launch(
+                    config_path,
+                    Event::ControlResult(result),
+                ))
+                .into()
+            },
+        )
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let status_text = match &self.status {
+            Status::Starting => "Starting".to_string(),
+            Status::Running(running) => {
+                let mut text = format!("Up {}", format_uptime(running.started.elapsed()));
+                if let Some(pid) = running.pid {
+                    text.push_str(&format!(" • PID {pid}"));
+                }
+                text
+            }
+            Status::Exited(Some(0)) => "Exited".to_string(),
+            Status::Exited(Some(code)) => format!("Exited with code {code}"),
+            Status::Exited(None) => "Terminated by signal".to_string(),
+            Status::Failed(e) => format!("Failed to launch: {e}"),
+        };
+
+        let column = widget::column()
+            .push(widget::text(status_text).class(match self.status {
+                Status::Exited(Some(0)) | Status::Starting | Status::Running(_) => {
+                    cosmic::style::Text::Default
+                }
+                _ => cosmic::style::Text::Accent,
+            }))
+            .push_maybe(
+                self.control_error
+                    .as_deref()
+                    .map(|e| widget::text(e).class(cosmic::style::Text::Accent)),
+            );
+
+        self.stderr
+            .iter()
+            .skip(self.stderr.len().saturating_sub(DISPLAYED_STDERR_LINES))
+            .fold(column, |column, line| {
+                column.push(widget::text::caption(line.as_str()))
+            })
+            .into()
+    }
+
+    /// Buttons controlling the lifecycle of a running VM.
+    pub(super) fn controls(&self, config_path: &Path) -> Option<Element<crate::app::Message>> {
+        let Status::Running(ref running) = self.status else {
+            return None;
+        };
+        let control = |icon_name: &'static str, tooltip: &'static str, action: Action| {
+            widget::button::icon(icon::from_name(icon_name))
+                .on_press(super::Message::Control(config_path.to_path_buf(), action).into())
+                .tooltip(tooltip)
+        };
+
+        let pause_resume = if running.qemu_status.as_deref() == Some("paused") {
+            control("media-playback-start-symbolic", "Resume", Action::Resume)
+        } else {
+            control("media-playback-pause-symbolic", "Pause", Action::Pause)
+        };
+
+        Some(
+            widget::row()
+                .align_y(Alignment::Center)
+                .push(pause_resume)
+                .push(control("view-refresh-symbolic", "Reset", Action::Reset))
+                .push(control(
+                    "system-shutdown-symbolic",
+                    "Shut Down",
+                    Action::Shutdown,
+                ))
+                .push(control("process-stop-symbolic", "Force Stop", Action::Stop))
+                .into(),
+        )
+    }
+}
+
+/// A badge showing whether a VM is currently running.
+pub(super) fn badge(launch: Option<&Launch>) -> Element<crate::app::Message> {
+    let (text, class) = match launch.map(|launch| &launch.status) {
+        Some(Status::Starting) => ("Starting", cosmic::style::Text::Accent),
+        Some(Status::Running(running)) => match running.qemu_status.as_deref() {
+            Some("paused") => ("Paused", cosmic::style::Text::Accent),
+            _ => ("Running", cosmic::style::Text::Accent),
+        },
+        _ => ("Stopped", cosmic::style::Text::Default),
+    };
+    widget::text::caption_heading(text).class(class).into()
+}
+
+/// Periodically queries the state of every running VM over QMP.
+pub(super) fn subscription<'a>(
+    launches: impl Iterator<Item = (&'a PathBuf, &'a Launch)>,
+) -> Subscription<crate::app::Message> {
+    struct StatusPoll;
+
+    let sockets: Vec<(PathBuf, PathBuf)> = launches
+        .filter_map(|(config_path, launch)| {
+            launch
+                .qmp_socket()
+                .map(|socket| (config_path.clone(), socket.clone()))
+        })
+        .collect();
+    if sockets.is_empty() {
+        return Subscription::none();
+    }
+
+    Subscription::run_with_id(
+        (std::any::TypeId::of::<StatusPoll>(), sockets.clone()),
+        cosmic::iced::stream::channel(4, move |mut output| async move {
+            let mut interval = tokio::time::interval(STATUS_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                for (config_path, socket) in &sockets {
+                    // The socket may not exist yet right after launch; the next poll will retry
+                    let Ok(status) = query_status(socket).await else {
+                        continue;
+                    };
+                    let msg =
+                        super::Message::Launch(config_path.clone(), Event::QemuStatus(status));
+                    _ = output.send(crate::app::Message::from(msg)).await;
+                }
+            }
+        }),
+    )
+}
+
+async fn query_status(socket: &Path) -> Result<String, crate::qmp::QmpError> {
+    let mut qmp = Qmp::connect(socket).await?;
+    let status = qmp.execute("query-status", None).await?;
+    Ok(status["status"].as_str().unwrap_or("unknown").to_string())
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    Started {
+        pid: Option<u32>,
+        qmp_socket: PathBuf,
+    },
+    Stderr(String),
+    QemuStatus(String),
+    ControlResult(Result<(), String>),
+    Exited(Option<i32>),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Requests a graceful shutdown through an ACPI power button press.
+    Shutdown,
+    /// Immediately terminates QEMU.
+    Stop,
+    Pause,
+    Resume,
+    Reset,
+}
+
+impl Action {
+    fn qmp_command(self) -> &'static str {
+        match self {
+            Action::Shutdown => "system_powerdown",
+            Action::Stop => "quit",
+            Action::Pause => "stop",
+            Action::Resume => "cont",
+            Action::Reset => "system_reset",
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget::{self, icon},
+    Element,
+};
+
+use crate::vm::{BootOrder, Vm};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Media {
+    vm: Vm,
+    installer_iso: Option<PathBuf>,
+    secondary_iso: Option<PathBuf>,
+    boot_order: BootOrder,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Drive {
+    Installer,
+    Secondary,
+}
+
+impl Media {
+    pub(super) fn new(vm: Vm) -> Self {
+        Self {
+            installer_iso: vm.installer_iso().map(ToOwned::to_owned),
+            secondary_iso: vm.secondary_iso().map(ToOwned::to_owned),
+            boot_order: vm.boot_order(),
+            error: None,
+            vm,
+        }
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    fn drive_mut(&mut self, drive: Drive) -> &mut Option<PathBuf> {
+        match drive {
+            Drive::Installer => &mut self.installer_iso,
+            Drive::Secondary => &mut self.secondary_iso,
+        }
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::SelectIso(drive) => {
+                return Task::perform(crate::app::select_file("Select ISO"), move |iso| {
+                    match iso {
+                        Some(iso) => crate::app::Message::from(Message::SelectedIso(drive, iso)),
+                        _ => crate::app::Message::None,
+                    }
+                    .into()
+                })
+            }
+            Message::SelectedIso(drive, iso) => *self.drive_mut(drive) = Some(iso),
+            Message::Detach(drive) => *self.drive_mut(drive) = None,
+            Message::SetBootOrder(boot_order) => self.boot_order = boot_order,
+            Message::Save => {
+                let mut vm = self.vm.clone();
+                vm.set_installer_iso(self.installer_iso.clone());
+                vm.set_secondary_iso(self.secondary_iso.clone());
+                vm.set_boot_order(self.boot_order);
+                let config_path = vm.config_path().to_owned();
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || vm.save())
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    move |result| {
+                        crate::app::Message::from(match result {
+                            Ok(()) => super::Message::VMEdited {
+                                old_config_path: config_path.clone(),
+                                new_config_path: config_path,
+                            },
+                            Err(e) => Message::Error(e).into(),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Error(e) => self.error = Some(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self, running: bool) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Media of {}", self.vm.name())));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM to change its media")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        list = list
+            .add(drive_row(
+                "Installer ISO:  ",
+                Drive::Installer,
+                self.installer_iso.as_deref(),
+            ))
+            .add(drive_row(
+                "Secondary ISO:  ",
+                Drive::Secondary,
+                self.secondary_iso.as_deref(),
+            ));
+
+        let boot_order = |label: &'static str, boot_order: BootOrder| {
+            widget::radio(label, boot_order, Some(self.boot_order), |boot_order| {
+                Message::SetBootOrder(boot_order).into()
+            })
+        };
+        list = list.add(
+            widget::column()
+                .push(widget::text::heading("Boot Order"))
+                .push(boot_order(
+                    "Automatic (installer media while attached)",
+                    BootOrder::Automatic,
+                ))
+                .push(boot_order("Disk first", BootOrder::DiskFirst))
+                .push(boot_order("Installer media first", BootOrder::MediaFirst)),
+        );
+
+        if let Some(error) = &self.error {
+            list = list.add(widget::text(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let missing_iso = [&self.installer_iso, &self.secondary_iso]
+                .into_iter()
+                .flatten()
+                .any(|iso| !iso.is_file());
+            let save = widget::button::suggested("Save")
+                .on_press_maybe((!running && !missing_iso).then(|| Message::Save.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(save)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+fn drive_row<'a>(
+    label: &'a str,
+    drive: Drive,
+    iso: Option<&Path>,
+) -> Element<'a, crate::app::Message> {
+    let iso_text = match iso {
+        Some(iso) if !iso.is_file() => {
+            widget::text(format!("{} (missing)", iso.display())).class(cosmic::style::Text::Accent)
+        }
+        Some(iso) => widget::text(iso.display().to_string()),
+        None => widget::text("None"),
+    };
+    let select = widget::button::icon(icon::from_name("media-optical-symbolic"))
+        .on_press(Message::SelectIso(drive).into())
+        .tooltip("Attach ISO");
+    let detach = widget::button::icon(icon::from_name("media-eject-symbolic"))
+        .on_press_maybe(iso.is_some().then(|| Message::Detach(drive).into()))
+        .tooltip("Detach ISO");
+
+    widget::row()
+        .align_y(Alignment::Center)
+        .push(widget::text(label))
+        .push(iso_text.width(Length::Fill))
+        .push(select)
+        .push(detach)
+        .into()
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SelectIso(Drive),
+    SelectedIso(Drive, PathBuf),
+    Detach(Drive),
+    SetBootOrder(BootOrder),
+    Save,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Media(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme,
+    widget::{self, icon},
+    Element,
+};
+use itertools::Itertools;
+
+use crate::config::VmMetadata;
+
+/// Icons which can be shown in place of the default one.
+const ICONS: &[&str] = &[
+    "computer-symbolic",
+    "network-server-symbolic",
+    "applications-development-symbolic",
+    "applications-games-symbolic",
+    "applications-science-symbolic",
+    "applications-multimedia-symbolic",
+    "security-high-symbolic",
+    "utilities-terminal-symbolic",
+];
+pub(super) const DEFAULT_ICON: &str = ICONS[0];
+
+#[derive(Debug, Clone)]
+pub(crate) struct EditMetadata {
+    config_path: PathBuf,
+    vm_name: String,
+    notes: String,
+    /// Tags as entered, separated by commas.
+    tags: String,
+    icon: Option<String>,
+    /// Metadata which isn't editable here, kept as it was.
+    original: VmMetadata,
+}
+
+impl EditMetadata {
+    pub(super) fn new(config_path: PathBuf, vm_name: String, metadata: &VmMetadata) -> Self {
+        Self {
+            config_path,
+            vm_name,
+            notes: metadata.notes.clone(),
+            tags: metadata.tags.join(", "),
+            icon: metadata.icon.clone(),
+            original: metadata.clone(),
+        }
+    }
+
+    fn metadata(&self) -> VmMetadata {
+        VmMetadata {
+            notes: self.notes.trim().to_string(),
+            tags: parse_tags(&self.tags),
+            icon: self.icon.clone(),
+            ..self.original.clone()
+        }
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::SetNotes(notes) => self.notes = notes,
+            Message::SetTags(tags) => self.tags = tags,
+            Message::SetIcon(icon) => self.icon = icon,
+            Message::Save => {
+                let config_path = self.config_path.clone();
+                let metadata = self.metadata();
+                return Task::perform(
+                    async move {
+                        crate::app::Message::from(super::Message::MetadataEdited {
+                            config_path,
+                            metadata,
+                        })
+                    },
+                    |msg| msg.into(),
+                );
+            }
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!("Details of {}", self.vm_name)));
+
+        list = list.add(
+            widget::column().push(widget::text::heading("Notes")).push(
+                widget::text_input("Anything worth remembering about this VM", &self.notes)
+                    .on_input(|notes| Message::SetNotes(notes).into()),
+            ),
+        );
+
+        list = list.add(
+            widget::column().push(widget::text::heading("Tags")).push(
+                widget::text_input("Separated by commas", &self.tags)
+                    .on_input(|tags| Message::SetTags(tags).into()),
+            ),
+        );
+
+        let current_icon = self.icon.as_deref().unwrap_or(DEFAULT_ICON);
+        let icons = ICONS.iter().fold(
+            widget::row().spacing(theme::active().cosmic().spacing.space_xxs),
+            |row, &name| {
+                // The default icon is stored as no icon, so that it can change in the future
+                let icon = (name != DEFAULT_ICON).then(|| name.to_string());
+                row.push(
+                    widget::button::icon(icon::from_name(name))
+                        .selected(name == current_icon)
+                        .on_press(Message::SetIcon(icon).into()),
+                )
+            },
+        );
+        list = list.add(
+            widget::column()
+                .push(widget::text::heading("Icon"))
+                .push(icons),
+        );
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+            let save = widget::button::suggested("Save").on_press(Message::Save.into());
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(save)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(ToOwned::to_owned)
+        .unique()
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    SetNotes(String),
+    SetTags(String),
+    SetIcon(Option<String>),
+    Save,
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Metadata(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    net::{Ipv4Addr, TcpListener, UdpSocket},
+    path::Path,
+};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme,
+    widget::{self, icon},
+    Element,
+};
+
+use crate::vm::{PortForward, Protocol, Vm};
+
+/// Binding to ports below this requires privileges which QEMU normally doesn't have.
+const FIRST_UNPRIVILEGED_PORT: u16 = 1024;
+
+#[derive(Debug, Clone)]
+pub(crate) struct PortForwards {
+    vm: Vm,
+    rules: Vec<Rule>,
+    /// The port forwards of every other registered VM, alongside its name.
+    other_vms: Vec<(String, Vec<PortForward>)>,
+    issues: Vec<Option<String>>,
+    /// Whether the host ports are being checked before saving.
+    checking: bool,
+    error: Option<String>,
+}
+
+/// A port forward being edited, with ports kept as entered.
+#[derive(Debug, Clone)]
+struct Rule {
+    protocol: Protocol,
+    host: String,
+    guest: String,
+}
+
+impl Rule {
+    fn parse(&self) -> Option<PortForward> {
+        Some(PortForward {
+            protocol: self.protocol,
+            host: parse_port(&self.host)?,
+            guest: parse_port(&self.guest)?,
+        })
+    }
+}
+
+impl PortForwards {
+    pub(super) fn new(vm: Vm, other_vms: Vec<(String, Vec<PortForward>)>) -> Self {
+        let rules = vm
+            .port_forwards()
+            .into_iter()
+            .map(|forward| Rule {
+                protocol: forward.protocol,
+                host: forward.host.to_string(),
+                guest: forward.guest.to_string(),
+            })
+            .collect();
+        let mut port_forwards = Self {
+            vm,
+            rules,
+            other_vms,
+            issues: vec![],
+            checking: false,
+            error: None,
+        };
+        port_forwards.validate();
+        port_forwards
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::AddRule => self.rules.push(Rule {
+                protocol: Protocol::Tcp,
+                host: String::new(),
+                guest: String::new(),
+            }),
+            Message::RemoveRule(index) => {
+                if index < self.rules.len() {
+                    self.rules.remove(index);
+                }
+            }
+            Message::SetProtocol(index, protocol) => {
+                if let Some(rule) = self.rules.get_mut(index) {
+                    rule.protocol = protocol;
+                }
+            }
+            Message::SetHostPort(index, port) => {
+                if let Some(rule) = self.rules.get_mut(index) {
+                    rule.host = port;
+                }
+            }
+            Message::SetGuestPort(index, port) => {
+                if let Some(rule) = self.rules.get_mut(index) {
+                    rule.guest = port;
+                }
+            }
+            Message::Save => {
+                let Some(forwards) = self.forwards() else {
+                    return Task::none();
+                };
+                // Binding sockets can be slow, so ports are only checked before saving. The VM
+                // isn't running at that point, so any port in use is held by something else.
+                self.checking = true;
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let issues: Vec<_> = forwards.iter().map(in_use_issue).collect();
+                            (forwards, issues)
+                        })
+                        .await
+                        .expect("Couldn't spawn thread")
+                    },
+                    |(forwards, issues)| {
+                        crate::app::Message::from(Message::Checked(forwards, issues)).into()
+                    },
+                );
+            }
+            Message::Checked(forwards, issues) => {
+                self.checking = false;
+                // The rules may have been edited in the meantime
+                if self.forwards().as_ref() != Some(&forwards) {
+                    return Task::none();
+                }
+                if issues.iter().any(Option::is_some) {
+                    self.issues = issues;
+                    return Task::none();
+                }
+                let mut vm = self.vm.clone();
+                vm.set_port_forwards(&forwards);
+                let config_path = vm.config_path().to_owned();
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || vm.save())
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    move |result| {
+                        crate::app::Message::from(match result {
+                            Ok(()) => super::Message::VMEdited {
+                                old_config_path: config_path.clone(),
+                                new_config_path: config_path,
+                            },
+                            Err(e) => Message::Error(e).into(),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Error(e) => {
+                self.checking = false;
+                self.error = Some(e);
+            }
+        }
+        self.validate();
+        Task::none()
+    }
+
+    fn forwards(&self) -> Option<Vec<PortForward>> {
+        self.rules.iter().map(Rule::parse).collect()
+    }
+
+    /// Finds a problem with each rule, if it has one. Whether host ports are free is only
+    /// checked when saving.
+    fn validate(&mut self) {
+        self.issues = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let Some(host) = parse_port(&rule.host) else {
+                    return Some("The host port must be a number from 1 to 65535".to_string());
+                };
+                if parse_port(&rule.guest).is_none() {
+                    return Some("The guest port must be a number from 1 to 65535".to_string());
+                }
+                if host < FIRST_UNPRIVILEGED_PORT {
+                    return Some(format!(
+                        "Host ports below {FIRST_UNPRIVILEGED_PORT} can't be used without root \
+                         privileges"
+                    ));
+                }
+
+                let same_port = |forward: &PortForward| {
+                    forward.protocol == rule.protocol && forward.host == host
+                };
+                if self.rules[..index]
+                    .iter()
+                    .filter_map(Rule::parse)
+                    .any(|forward| same_port(&forward))
+                {
+                    return Some(format!(
+                        "Host port {host}/{} is already forwarded above",
+                        rule.protocol
+                    ));
+                }
+                if let Some((name, _)) = self
+                    .other_vms
+                    .iter()
+                    .find(|(_, forwards)| forwards.iter().any(same_port))
+                {
+                    return Some(format!(
+                        "Host port {host}/{} is already used by {name}",
+                        rule.protocol
+                    ));
+                }
+                None
+            })
+            .collect();
+    }
+
+    pub(super) fn view(&self, running: bool) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!(
+            "Port Forwards of {}",
+            self.vm.name()
+        )));
+        list = list.add(widget::text::caption(
+            "Forwarded ports reach the guest through user-mode networking. Changes take effect \
+             the next time the VM starts.",
+        ));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM to change its port forwards")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        let rule_list = if self.rules.is_empty() {
+            widget::list_column().add(widget::text("No ports are forwarded"))
+        } else {
+            self.rules.iter().zip(&self.issues).enumerate().fold(
+                widget::list_column().style(theme::Container::ContextDrawer),
+                |rule_list, (index, (rule, issue))| {
+                    let protocol = |protocol: Protocol| {
+                        widget::radio(
+                            protocol.to_string(),
+                            protocol,
+                            Some(rule.protocol),
+                            move |protocol| Message::SetProtocol(index, protocol).into(),
+                        )
+                    };
+                    let row = widget::row()
+                        .align_y(Alignment::Center)
+                        .spacing(theme::active().cosmic().spacing.space_xs)
+                        .push(protocol(Protocol::Tcp))
+                        .push(protocol(Protocol::Udp))
+                        .push(
+                            widget::text_input("Host port", rule.host.as_str())
+                                .on_input(move |port| Message::SetHostPort(index, port).into()),
+                        )
+                        .push(widget::text("→"))
+                        .push(
+                            widget::text_input("Guest port", rule.guest.as_str())
+                                .on_input(move |port| Message::SetGuestPort(index, port).into()),
+                        )
+                        .push(
+                            widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .on_press(Message::RemoveRule(index).into())
+                                .tooltip("Remove Forward"),
+                        );
+
+                    rule_list.add(widget::column().push(row).push_maybe(issue.as_deref().map(
+                        |issue| widget::text::caption(issue).class(cosmic::style::Text::Accent),
+                    )))
+                },
+            )
+        };
+        list = list.add(widget::scrollable(rule_list).height(Length::Fill));
+
+        list = list.add(widget::button::standard("Add Forward").on_press(Message::AddRule.into()));
+
+        if let Some(error) = &self.error {
+            list = list.add(widget::text(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let can_save = !running && !self.checking && self.issues.iter().all(Option::is_none);
+            let save = widget::button::suggested("Save")
+                .on_press_maybe(can_save.then(|| Message::Save.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(save)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+fn parse_port(port: &str) -> Option<u16> {
+    port.trim().parse().ok().filter(|port| *port != 0)
+}
+
+/// Why the forward's host port can't be used, if another process is listening on it. QEMU binds
+/// forwarded ports on every interface, so that's checked here too.
+fn in_use_issue(forward: &PortForward) -> Option<String> {
+    let in_use = |e: std::io::Error| e.kind() == std::io::ErrorKind::AddrInUse;
+    let bound = match forward.protocol {
+        Protocol::Tcp => {
+            TcpListener::bind((Ipv4Addr::UNSPECIFIED, forward.host)).is_err_and(in_use)
+        }
+        Protocol::Udp => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, forward.host)).is_err_and(in_use),
+    };
+    bound.then(|| {
+        format!(
+            "Host port {}/{} is already in use on this computer",
+            forward.host, forward.protocol
+        )
+    })
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    AddRule,
+    RemoveRule(usize),
+    SetProtocol(usize, Protocol),
+    SetHostPort(usize, String),
+    SetGuestPort(usize, String),
+    Save,
+    /// The forwards checked before saving, and whether each host port is in use.
+    Checked(Vec<PortForward>, Vec<Option<String>>),
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::PortForwards(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
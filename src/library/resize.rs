@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    widget, Element,
+};
+use size::Size;
+
+use crate::{
+    creation::options::size_row,
+    qemu_img::{self, ImageInfo},
+    vm::Vm,
+};
+
+const MIN_DISK_SIZE: f64 = size::consts::GiB as f64;
+/// Disk images can always grow to at least this size, regardless of their current size.
+const MIN_MAX_DISK_SIZE: f64 = 512.0 * size::consts::GiB as f64;
+
+#[derive(Debug, Clone)]
+pub(crate) struct ResizeDisk {
+    vm: Vm,
+    disk: Option<PathBuf>,
+    info: Option<ImageInfo>,
+    size: f64,
+    confirm_shrink: bool,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Resizing,
+    Failed(String),
+}
+
+impl ResizeDisk {
+    pub(super) fn new(vm: Vm) -> (Self, Task<crate::app::Message>) {
+        let disk = vm.disk_images().into_iter().next();
+        let resize = Self {
+            vm,
+            disk,
+            info: None,
+            size: 0.0,
+            confirm_shrink: false,
+            status: Status::Idle,
+        };
+        let task = resize.load_info();
+        (resize, task)
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    fn load_info(&self) -> Task<crate::app::Message> {
+        let Some(disk) = self.disk.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || qemu_img::info(&disk))
+                    .await
+                    .expect("Couldn't spawn thread")
+            },
+            |info| {
+                crate::app::Message::from(match info {
+                    Ok(info) => Message::Info(info),
+                    Err(e) => Message::Error(e),
+                })
+                .into()
+            },
+        )
+    }
+
+    fn is_shrink(&self) -> bool {
+        self.info
+            .as_ref()
+            .is_some_and(|info| (self.size as u64) < info.virtual_size)
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Info(info) => {
+                self.size = info.virtual_size as f64;
+                self.info = Some(info);
+            }
+            Message::SetSize(size) => {
+                // Sizes are kept to whole MiB so that they're valid for any image format
+                let mib = size::consts::MiB as f64;
+                self.size = (size / mib).round() * mib;
+                self.confirm_shrink = false;
+            }
+            Message::SetConfirmShrink(confirm_shrink) => self.confirm_shrink = confirm_shrink,
+            Message::Resize => {
+                let Some(disk) = self.disk.clone() else {
+                    return Task::none();
+                };
+                let size = self.size as u64;
+                let shrink = self.is_shrink();
+                self.status = Status::Resizing;
+
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || qemu_img::resize(&disk, size, shrink))
+                            .await
+                            .expect("Couldn't spawn thread")
+                    },
+                    |result| {
+                        crate::app::Message::from(match result {
+                            Ok(()) => Message::Resized,
+                            Err(e) => Message::Error(e),
+                        })
+                        .into()
+                    },
+                );
+            }
+            Message::Resized => {
+                self.status = Status::Idle;
+                self.confirm_shrink = false;
+                return self.load_info();
+            }
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(
+        &self,
+        running: bool,
+        linked_clones: &[String],
+    ) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!(
+            "Resize Disk of {}",
+            self.vm.name()
+        )));
+        if running {
+            list = list.add(
+                widget::text("Shut down the VM before resizing its disk")
+                    .class(cosmic::style::Text::Accent),
+            );
+        }
+        if !linked_clones.is_empty() {
+            list = list.add(
+                widget::text(format!(
+                    "Linked clones depend on this disk, so it can't be resized: {}",
+                    linked_clones.join(", ")
+                ))
+                .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        match (&self.disk, &self.info) {
+            (None, _) => list = list.add(widget::text("This VM has no disk image")),
+            (Some(_), None) => {
+                if self.status == Status::Idle {
+                    list = list.add(widget::text("Reading disk image"));
+                }
+            }
+            (Some(disk), Some(info)) => {
+                list = list
+                    .add(widget::text::caption(disk.display().to_string()))
+                    .add(widget::text(format!(
+                        "Current size: {} ({} allocated)",
+                        Size::from_bytes(info.virtual_size),
+                        info.actual_size.map_or_else(
+                            || "unknown".to_string(),
+                            |size| Size::from_bytes(size).to_string()
+                        )
+                    )));
+
+                let max_size = (info.virtual_size as f64 * 4.0).max(MIN_MAX_DISK_SIZE);
+                list = list.add(size_row(
+                    "Disk Size:  ",
+                    MIN_DISK_SIZE..=max_size,
+                    self.size,
+                    |size| Message::SetSize(size).into(),
+                ));
+
+                if self.is_shrink() {
+                    list = list
+                        .add(
+                            widget::text(
+                                "Shrinking discards all data beyond the new size. Shrink the \
+                                 partitions and filesystems within the guest first.",
+                            )
+                            .class(cosmic::style::Text::Accent),
+                        )
+                        .add(
+                            widget::checkbox(
+                                "The guest's partitions fit within the new size",
+                                self.confirm_shrink,
+                            )
+                            .on_toggle(|confirm| Message::SetConfirmShrink(confirm).into()),
+                        );
+                }
+            }
+        }
+
+        match &self.status {
+            Status::Resizing => list = list.add(widget::text("Resizing disk image")),
+            Status::Failed(e) => {
+                list = list.add(
+                    widget::text(format!("Couldn't resize disk image: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                )
+            }
+            Status::Idle => {}
+        }
+
+        list = list.add(widget::vertical_space());
+
+        let nav_row = {
+            let back = widget::button::suggested("Back").on_press(super::Message::ShowList.into());
+
+            let changed = self
+                .info
+                .as_ref()
+                .is_some_and(|info| self.size as u64 != info.virtual_size);
+            let can_resize = !running
+                && linked_clones.is_empty()
+                && changed
+                && self.status != Status::Resizing
+                && (!self.is_shrink() || self.confirm_shrink);
+            let resize = if self.is_shrink() {
+                widget::button::destructive("Shrink")
+            } else {
+                widget::button::suggested("Grow")
+            }
+            .on_press_maybe(can_resize.then(|| Message::Resize.into()));
+
+            widget::row().align_y(Alignment::Center).push(back).push(
+                widget::container(resize)
+                    .align_right(Length::Shrink)
+                    .width(Length::Fill),
+            )
+        };
+        list = list.add(nav_row);
+
+        list.into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Info(ImageInfo),
+    SetSize(f64),
+    SetConfirmShrink(bool),
+    Resize,
+    Resized,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Resize(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
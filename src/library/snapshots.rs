@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme,
+    widget::{self, icon},
+    Element,
+};
+use serde_json::json;
+use size::Size;
+
+use crate::{
+    qemu_img::{self, Snapshot},
+    qmp::Qmp,
+    vm::Vm,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshots {
+    vm: Vm,
+    disk: Option<PathBuf>,
+    snapshots: Option<Vec<Snapshot>>,
+    label: String,
+    pending_confirmation: Option<Operation>,
+    status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Idle,
+    Working,
+    Failed(String),
+}
+
+/// A change to the VM's snapshots. While the VM is running, these are carried out by QEMU
+/// itself, which also captures or restores the VM's memory state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Create(String),
+    Revert(String),
+    Delete(String),
+}
+
+impl Operation {
+    async fn run(self, disk: PathBuf, qmp_socket: Option<PathBuf>) -> Result<(), String> {
+        match qmp_socket {
+            Some(qmp_socket) => {
+                let command_line = match self {
+                    Operation::Create(name) => format!("savevm {name}"),
+                    Operation::Revert(name) => format!("loadvm {name}"),
+                    Operation::Delete(name) => format!("delvm {name}"),
+                };
+                let mut qmp = Qmp::connect(&qmp_socket).await.map_err(|e| e.to_string())?;
+                let output = qmp
+                    .execute(
+                        "human-monitor-command",
+                        Some(json!({ "command-line": command_line })),
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+                // Monitor commands only produce output when something went wrong
+                match output.as_str().map(str::trim) {
+                    Some("") | None => Ok(()),
+                    Some(output) => Err(output.to_string()),
+                }
+            }
+            None => tokio::task::spawn_blocking(move || match self {
+                Operation::Create(name) => qemu_img::create_snapshot(&disk, &name),
+                Operation::Revert(name) => qemu_img::apply_snapshot(&disk, &name),
+                Operation::Delete(name) => qemu_img::delete_snapshot(&disk, &name),
+            })
+            .await
+            .expect("Couldn't spawn thread"),
+        }
+    }
+}
+
+impl Snapshots {
+    pub(super) fn new(vm: Vm) -> (Self, Task<crate::app::Message>) {
+        let disk = vm.disk_images().into_iter().next();
+        let snapshots = Self {
+            vm,
+            disk,
+            snapshots: None,
+            label: String::new(),
+            pending_confirmation: None,
+            status: Status::Idle,
+        };
+        let task = snapshots.list();
+        (snapshots, task)
+    }
+
+    pub(super) fn config_path(&self) -> &Path {
+        self.vm.config_path()
+    }
+
+    fn list(&self) -> Task<crate::app::Message> {
+        let Some(disk) = self.disk.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || qemu_img::info(&disk))
+                    .await
+                    .expect("Couldn't spawn thread")
+            },
+            |info| {
+                crate::app::Message::from(match info {
+                    Ok(info) => Message::Listed(info.snapshots),
+                    Err(e) => Message::Error(e),
+                })
+                .into()
+            },
+        )
+    }
+
+    pub(super) fn update(
+        &mut self,
+        msg: Message,
+        qmp_socket: Option<PathBuf>,
+    ) -> Task<crate::app::Message> {
+        match msg {
+            Message::Listed(snapshots) => self.snapshots = Some(snapshots),
+            Message::SetLabel(label) => self.label = label,
+            Message::Request(operation) => self.pending_confirmation = Some(operation),
+            Message::CancelRequest => self.pending_confirmation = None,
+            Message::Run(operation) => {
+                let Some(disk) = self.disk.clone() else {
+                    return Task::none();
+                };
+                if let Operation::Create(name) = &operation {
+                    if let Some(issue) = self.label_issue(name) {
+                        self.status = Status::Failed(issue);
+                        return Task::none();
+                    }
+                }
+                self.pending_confirmation = None;
+                self.status = Status::Working;
+                return Task::perform(operation.run(disk, qmp_socket), |result| {
+                    crate::app::Message::from(match result {
+                        Ok(()) => Message::Done,
+                        Err(e) => Message::Error(e),
+                    })
+                    .into()
+                });
+            }
+            Message::Done => {
+                self.status = Status::Idle;
+                self.label.clear();
+                return self.list();
+            }
+            Message::Error(e) => self.status = Status::Failed(e),
+        }
+        Task::none()
+    }
+
+    /// Why a snapshot can't be created under the label, if it can't. QEMU would otherwise
+    /// silently replace an existing snapshot with the same name.
+    fn label_issue(&self, label: &str) -> Option<String> {
+        if label.contains(char::is_whitespace) {
+            Some("Snapshot labels can't contain spaces".to_string())
+        } else if self
+            .snapshots
+            .iter()
+            .flatten()
+            .any(|snapshot| snapshot.name == label)
+        {
+            Some(format!("A snapshot named {label} already exists"))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn view(&self, linked_clones: &[String]) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4(format!(
+            "Snapshots of {}",
+            self.vm.name()
+        )));
+
+        let Some(disk) = &self.disk else {
+            return list
+                .add(widget::text("This VM has no disk image"))
+                .add(widget::button::suggested("Back").on_press(super::Message::ShowList.into()))
+                .into();
+        };
+        list = list.add(widget::text::caption(disk.display().to_string()));
+        // Reverting rewrites the disk's contents, which linked clones are built upon
+        let can_revert = linked_clones.is_empty();
+        if !can_revert {
+            list = list.add(
+                widget::text(format!(
+                    "Linked clones depend on this disk, so snapshots can't be reverted to: {}",
+                    linked_clones.join(", ")
+                ))
+                .class(cosmic::style::Text::Accent),
+            );
+        }
+
+        let idle = self.status != Status::Working;
+        let label_issue = self.label_issue(&self.label);
+        let valid_label = !self.label.is_empty() && label_issue.is_none();
+        let create_row = widget::row()
+            .align_y(Alignment::Center)
+            .push(
+                widget::text_input("Snapshot label", self.label.as_str())
+                    .on_input(|label| Message::SetLabel(label).into()),
+            )
+            .push(
+                widget::button::standard("Create Snapshot").on_press_maybe(
+                    (idle && valid_label)
+                        .then(|| Message::Run(Operation::Create(self.label.clone())).into()),
+                ),
+            );
+        list = list.add(create_row);
+        if let Some(issue) = label_issue {
+            list = list.add(widget::text::caption(issue));
+        }
+
+        if let Some(operation) = &self.pending_confirmation {
+            let prompt = match operation {
+                Operation::Revert(name) => {
+                    format!("Revert to {name}? Changes made since it was taken will be lost.")
+                }
+                Operation::Delete(name) => format!("Delete snapshot {name}?"),
+                Operation::Create(name) => format!("Create snapshot {name}?"),
+            };
+            list = list.add(
+                widget::row()
+                    .align_y(Alignment::Center)
+                    .push(widget::text(prompt).width(Length::Fill))
+                    .push(
+                        widget::button::standard("Cancel").on_press(Message::CancelRequest.into()),
+                    )
+                    .push(
+                        widget::button::destructive("Confirm").on_press_maybe(
+                            (idle && (can_revert || !matches!(operation, Operation::Revert(_))))
+                                .then(|| Message::Run(operation.clone()).into()),
+                        ),
+                    ),
+            );
+        }
+
+        match &self.status {
+            Status::Working => list = list.add(widget::text("Working")),
+            Status::Failed(e) => {
+                list = list.add(widget::text(e.as_str()).class(cosmic::style::Text::Accent))
+            }
+            Status::Idle => {}
+        }
+
+        let snapshot_list = match &self.snapshots {
+            None => widget::list_column().add(widget::text("Loading snapshots")),
+            Some(snapshots) if snapshots.is_empty() => {
+                widget::list_column().add(widget::text("No snapshots have been taken"))
+            }
+            Some(snapshots) => snapshots.iter().fold(
+                widget::list_column().style(theme::Container::ContextDrawer),
+                |snapshot_list, snapshot| {
+                    snapshot_list.add(snapshot_view(snapshot, idle, can_revert))
+                },
+            ),
+        };
+        list = list.add(widget::scrollable(snapshot_list).height(Length::Fill));
+
+        list =
+            list.add(widget::button::suggested("Back").on_press(super::Message::ShowList.into()));
+
+        list.into()
+    }
+}
+
+fn snapshot_view(
+    snapshot: &Snapshot,
+    idle: bool,
+    can_revert: bool,
+) -> Element<crate::app::Message> {
+    let date = DateTime::from_timestamp(snapshot.date_sec, snapshot.date_nsec)
+        .map(|date| {
+            date.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let size = match snapshot.vm_state_size {
+        0 => "Disk only".to_string(),
+        size => format!("VM state size: {}", Size::from_bytes(size)),
+    };
+
+    let details = widget::column()
+        .push(widget::text::heading(snapshot.name.as_str()))
+        .push(widget::text::caption(format!("{date} • {size}")))
+        .width(Length::Fill);
+
+    let revert = widget::button::icon(icon::from_name("edit-undo-symbolic"))
+        .on_press_maybe(
+            (idle && can_revert)
+                .then(|| Message::Request(Operation::Revert(snapshot.name.clone())).into()),
+        )
+        .tooltip("Revert to Snapshot");
+    let delete = widget::button::icon(icon::from_name("user-trash-symbolic"))
+        .on_press_maybe(
+            idle.then(|| Message::Request(Operation::Delete(snapshot.name.clone())).into()),
+        )
+        .tooltip("Delete Snapshot");
+
+    widget::row()
+        .align_y(Alignment::Center)
+        .push(details)
+        .push(revert)
+        .push(delete)
+        .into()
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Listed(Vec<Snapshot>),
+    SetLabel(String),
+    Request(Operation),
+    CancelRequest,
+    Run(Operation),
+    Done,
+    Error(String),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Snapshots(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use cosmic::{
+    app::command::Task,
+    iced::{Alignment, Length},
+    theme, widget, Element,
+};
+use size::Size;
+
+use crate::{
+    storage::{self, VmUsage},
+    vm::Vm,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct StorageUsage {
+    default_vm_dir: PathBuf,
+    usage: Option<Vec<(String, Result<VmUsage, String>)>>,
+    free_space: Option<u64>,
+}
+
+impl StorageUsage {
+    pub(super) fn new(vms: Vec<Vm>, default_vm_dir: PathBuf) -> (Self, Task<crate::app::Message>) {
+        let free_space_dir = default_vm_dir.clone();
+        let task = Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let mut usage: Vec<_> = vms
+                        .iter()
+                        .map(|vm| (vm.name().to_string(), storage::vm_usage(vm)))
+                        .collect();
+                    // The heaviest VMs are the most interesting, so they're listed first
+                    usage.sort_by_key(|(_, usage)| {
+                        std::cmp::Reverse(usage.as_ref().map_or(0, VmUsage::total))
+                    });
+                    (usage, storage::free_space(&free_space_dir))
+                })
+                .await
+                .expect("Couldn't spawn thread")
+            },
+            |(usage, free_space)| {
+                crate::app::Message::from(Message::Calculated(usage, free_space)).into()
+            },
+        );
+        (
+            Self {
+                default_vm_dir,
+                usage: None,
+                free_space: None,
+            },
+            task,
+        )
+    }
+
+    pub(super) fn update(&mut self, msg: Message) -> Task<crate::app::Message> {
+        match msg {
+            Message::Calculated(usage, free_space) => {
+                self.usage = Some(usage);
+                self.free_space = free_space;
+            }
+        }
+        Task::none()
+    }
+
+    pub(super) fn view(&self) -> Element<crate::app::Message> {
+        let mut list = widget::list_column();
+
+        list = list.add(widget::text::title4("Storage Usage"));
+
+        let Some(usage) = &self.usage else {
+            return list
+                .add(widget::text("Calculating storage usage"))
+                .add(widget::button::suggested("Back").on_press(super::Message::ShowList.into()))
+                .into();
+        };
+
+        let total: u64 = usage
+            .iter()
+            .filter_map(|(_, usage)| usage.as_ref().ok())
+            .map(VmUsage::total)
+            .sum();
+        list = list.add(widget::text::heading(format!(
+            "{} used by {} VMs",
+            Size::from_bytes(total),
+            usage.len()
+        )));
+        let free_space = match self.free_space {
+            Some(free_space) => format!(
+                "{} free in {}",
+                Size::from_bytes(free_space),
+                self.default_vm_dir.display()
+            ),
+            None => format!(
+                "Couldn't determine free space in {}",
+                self.default_vm_dir.display()
+            ),
+        };
+        list = list.add(widget::text(free_space));
+
+        let vm_list = usage.iter().fold(
+            widget::list_column().style(theme::Container::ContextDrawer),
+            |vm_list, (name, usage)| {
+                let details = match usage {
+                    Ok(usage) => widget::text::caption(format!(
+                        "Disk image: {} allocated of {} • Installer media: {} • Other: {}",
+                        Size::from_bytes(usage.disk_allocated),
+                        Size::from_bytes(usage.disk_virtual),
+                        Size::from_bytes(usage.installer_media),
+                        Size::from_bytes(usage.other),
+                    )),
+                    Err(e) => widget::text::caption(format!("Couldn't read VM files: {e}"))
+                        .class(cosmic::style::Text::Accent),
+                };
+                let total = usage.as_ref().map_or_else(
+                    |_| String::new(),
+                    |usage| Size::from_bytes(usage.total()).to_string(),
+                );
+
+                vm_list.add(
+                    widget::row()
+                        .align_y(Alignment::Center)
+                        .push(
+                            widget::column()
+                                .push(widget::text::heading(name.as_str()))
+                                .push(details)
+                                .width(Length::Fill),
+                        )
+                        .push(widget::text::heading(total)),
+                )
+            },
+        );
+        list = list.add(widget::scrollable(vm_list).height(Length::Fill));
+
+        list =
+            list.add(widget::button::suggested("Back").on_press(super::Message::ShowList.into()));
+
+        list.into()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Message {
+    Calculated(Vec<(String, Result<VmUsage, String>)>, Option<u64>),
+}
+
+impl From<Message> for super::Message {
+    fn from(value: Message) -> Self {
+        super::Message::Usage(value)
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(value: Message) -> Self {
+        crate::app::Message::Library(value.into())
+    }
+}
@@ -1,9 +1,19 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod app;
+mod archive;
+mod catalog;
 mod config;
 mod creation;
 mod i18n;
+mod library;
+mod qemu_img;
+mod qmp;
+mod serial;
+mod storage;
+#[cfg(test)]
+mod test_util;
+mod vm;
 
 fn main() -> cosmic::iced::Result {
     // Get the system's preferred languages.
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Wrappers around the `qemu-img` tool, used to inspect and modify VM disk images.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+/// Details about a disk image, as reported by `qemu-img info`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ImageInfo {
+    pub format: String,
+    pub virtual_size: u64,
+    pub actual_size: Option<u64>,
+    /// The image this one is an overlay of, as an absolute path.
+    pub full_backing_filename: Option<PathBuf>,
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// An internal snapshot stored within a qcow2 image.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Snapshot {
+    pub id: String,
+    pub name: String,
+    pub vm_state_size: u64,
+    pub date_sec: i64,
+    pub date_nsec: u32,
+}
+
+pub fn info(image: &Path) -> Result<ImageInfo, String> {
+    // Sharing allows inspecting images which are in use by a running VM
+    let output = run([
+        OsStr::new("info"),
+        OsStr::new("--force-share"),
+        OsStr::new("--output=json"),
+        image.as_os_str(),
+    ])?;
+    serde_json::from_str(&output).map_err(|e| format!("Invalid output from qemu-img: {e}"))
+}
+
+pub fn create_snapshot(image: &Path, name: &str) -> Result<(), String> {
+    snapshot(image, "-c", name)
+}
+
+pub fn apply_snapshot(image: &Path, name: &str) -> Result<(), String> {
+    snapshot(image, "-a", name)
+}
+
+pub fn delete_snapshot(image: &Path, name: &str) -> Result<(), String> {
+    snapshot(image, "-d", name)
+}
+
+fn snapshot(image: &Path, operation: &str, name: &str) -> Result<(), String> {
+    run([
+        OsStr::new("snapshot"),
+        OsStr::new(operation),
+        OsStr::new(name),
+        image.as_os_str(),
+    ])
+    .map(|_| ())
+}
+
+/// Changes the virtual size of an image. Shrinking must be explicitly allowed, since any data
+/// beyond the new size is lost.
+pub fn resize(image: &Path, size: u64, allow_shrink: bool) -> Result<(), String> {
+    let size = size.to_string();
+    let mut args = vec![OsStr::new("resize")];
+    if allow_shrink {
+        args.push(OsStr::new("--shrink"));
+    }
+    args.extend([image.as_os_str(), OsStr::new(&size)]);
+    run(args).map(|_| ())
+}
+
+/// Creates a qcow2 overlay which uses another image, in the given format, as its backing file.
+pub fn create_overlay(
+    backing_file: &Path,
+    backing_format: &str,
+    destination: &Path,
+) -> Result<(), String> {
+    run([
+        OsStr::new("create"),
+        OsStr::new("-f"),
+        OsStr::new("qcow2"),
+        OsStr::new("-F"),
+        OsStr::new(backing_format),
+        OsStr::new("-b"),
+        backing_file.as_os_str(),
+        destination.as_os_str(),
+    ])
+    .map(|_| ())
+}
+
+/// Copies an image into a standalone one of the same format, merging in its backing files.
+pub fn flatten(image: &Path, format: &str, destination: &Path) -> Result<(), String> {
+    run([
+        OsStr::new("convert"),
+        OsStr::new("-O"),
+        OsStr::new(format),
+        image.as_os_str(),
+        destination.as_os_str(),
+    ])
+    .map(|_| ())
+}
+
+fn run<'a>(args: impl IntoIterator<Item = &'a OsStr>) -> Result<String, String> {
+    let output = Command::new("qemu-img")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Couldn't run qemu-img: {e}"))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! A minimal client for the QEMU Machine Protocol, used to control running VMs.
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+        UnixStream,
+    },
+};
+
+pub struct Qmp {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Qmp {
+    /// Connects to a QMP socket and negotiates capabilities, leaving the connection in command mode.
+    pub async fn connect(socket: &Path) -> Result<Self, QmpError> {
+        let (reader, writer) = UnixStream::connect(socket).await?.into_split();
+        let mut qmp = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        // QEMU greets every new connection before it accepts any commands
+        qmp.read_message().await?;
+        qmp.execute("qmp_capabilities", None).await?;
+
+        Ok(qmp)
+    }
+
+    pub async fn execute(
+        &mut self,
+        command: &str,
+        arguments: Option<Value>,
+    ) -> Result<Value, QmpError> {
+        let mut request = json!({ "execute": command });
+        if let Some(arguments) = arguments {
+            request["arguments"] = arguments;
+        }
+        let mut request = serde_json::to_vec(&request)?;
+        request.push(b'\n');
+        self.writer.write_all(&request).await?;
+
+        loop {
+            let mut message = self.read_message().await?;
+            if let Some(error) = message.get("error") {
+                let desc = error["desc"].as_str().unwrap_or("Unknown error");
+                return Err(QmpError::Command(desc.to_string()));
+            }
+            if let Some(value) = message.get_mut("return") {
+                return Ok(value.take());
+            }
+            // Asynchronous events may arrive before the response; they aren't needed here
+        }
+    }
+
+    async fn read_message(&mut self) -> Result<Value, QmpError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(QmpError::Disconnected);
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+#[derive(Debug, derive_more::From)]
+pub enum QmpError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    #[from(ignore)]
+    Command(String),
+    #[from(ignore)]
+    Disconnected,
+}
+
+impl std::error::Error for QmpError {}
+impl std::fmt::Display for QmpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QmpError::Io(e) => write!(f, "Error communicating with QEMU: {}", e),
+            QmpError::Json(e) => write!(f, "Invalid response from QEMU: {}", e),
+            QmpError::Command(e) => write!(f, "QEMU returned an error: {}", e),
+            QmpError::Disconnected => write!(f, "QEMU closed the connection"),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Rendering of a VM's serial console output as plain lines of text.
+
+use std::collections::VecDeque;
+
+/// Only the tail of the console output is kept, to bound memory usage of long running VMs.
+const MAX_LINES: usize = 5000;
+const TAB_WIDTH: usize = 8;
+
+/// A minimal terminal which understands line endings, carriage returns and backspaces, and
+/// discards escape sequences. This is enough to follow boot logs and interact with a shell.
+#[derive(Debug, Clone, Default)]
+pub struct Terminal {
+    lines: VecDeque<String>,
+    current: Vec<char>,
+    column: usize,
+    /// Bytes of a UTF-8 sequence split across reads.
+    pending: Vec<u8>,
+    escape: Escape,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Escape {
+    #[default]
+    None,
+    /// An ESC byte has just been read.
+    Start,
+    /// Within a control sequence, which ends at a byte in the range `@` to `~`.
+    Csi,
+    /// Within an operating system command, which ends at BEL or ESC `\`.
+    Osc,
+}
+
+impl Terminal {
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let pending = std::mem::take(&mut self.pending);
+
+        let mut bytes = pending.as_slice();
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => {
+                    text.chars().for_each(|c| self.push(c));
+                    bytes = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, invalid) = bytes.split_at(e.valid_up_to());
+                    std::str::from_utf8(valid)
+                        .unwrap_or_default()
+                        .chars()
+                        .for_each(|c| self.push(c));
+                    match e.error_len() {
+                        Some(len) => {
+                            self.push(char::REPLACEMENT_CHARACTER);
+                            bytes = &invalid[len..];
+                        }
+                        // The rest of the character arrives with the next read
+                        None => {
+                            bytes = invalid;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = bytes.to_vec();
+    }
+
+    fn push(&mut self, c: char) {
+        match (self.escape, c) {
+            (Escape::None, '\x1b') => self.escape = Escape::Start,
+            (Escape::None, '\n') => self.new_line(),
+            (Escape::None, '\r') => self.column = 0,
+            (Escape::None, '\x08') => self.column = self.column.saturating_sub(1),
+            (Escape::None, '\t') => {
+                let spaces = TAB_WIDTH - self.column % TAB_WIDTH;
+                (0..spaces).for_each(|_| self.put(' '));
+            }
+            (Escape::None, c) if c.is_control() => {}
+            (Escape::None, c) => self.put(c),
+            (Escape::Start, '[') => self.escape = Escape::Csi,
+            (Escape::Start, ']') => self.escape = Escape::Osc,
+            (Escape::Start, _) => self.escape = Escape::None,
+            (Escape::Csi, c) if ('@'..='~').contains(&c) => {
+                // Erasing to the end of the line is commonly used when redrawing prompts
+                if c == 'K' {
+                    self.current.truncate(self.column);
+                }
+                self.escape = Escape::None;
+            }
+            (Escape::Csi, _) => {}
+            (Escape::Osc, '\x07') => self.escape = Escape::None,
+            (Escape::Osc, '\x1b') => self.escape = Escape::Start,
+            (Escape::Osc, _) => {}
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        match self.current.get_mut(self.column) {
+            Some(existing) => *existing = c,
+            None => {
+                self.current.resize(self.column, ' ');
+                self.current.push(c);
+            }
+        }
+        self.column += 1;
+    }
+
+    fn new_line(&mut self) {
+        if self.lines.len() >= MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(self.current.drain(..).collect());
+        self.column = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.current.is_empty()
+    }
+
+    /// The most recent lines of output, including the incomplete line being written.
+    pub fn tail(&self, lines: usize) -> String {
+        let skip = (self.lines.len() + 1).saturating_sub(lines);
+        let mut text = self
+            .lines
+            .iter()
+            .skip(skip)
+            .fold(String::new(), |mut text, line| {
+                text.push_str(line);
+                text.push('\n');
+                text
+            });
+        text.extend(&self.current);
+        text
+    }
+
+    /// All retained output, for copying to the clipboard.
+    pub fn text(&self) -> String {
+        self.tail(usize::MAX)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(chunks: &[&[u8]]) -> String {
+        let mut terminal = Terminal::default();
+        chunks.iter().for_each(|chunk| terminal.feed(chunk));
+        terminal.text()
+    }
+
+    #[test]
+    fn line_feeds_start_new_lines() {
+        assert_eq!(render(&[b"first\nsecond\n"]), "first\nsecond\n");
+    }
+
+    #[test]
+    fn carriage_returns_overwrite_the_line() {
+        assert_eq!(render(&[b"loading 10%\rloading 100%\n"]), "loading 100%\n");
+        assert_eq!(render(&[b"abcdef\rXY"]), "XYcdef");
+    }
+
+    #[test]
+    fn characters_split_across_reads_are_joined() {
+        let text = "héllo ✓".as_bytes();
+        let chunks: Vec<&[u8]> = text.chunks(1).collect();
+        assert_eq!(render(&chunks), "héllo ✓");
+    }
+
+    #[test]
+    fn invalid_bytes_are_replaced() {
+        assert_eq!(render(&[b"a\xffb"]), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn escape_sequences_are_stripped() {
+        assert_eq!(render(&[b"\x1b[1;32mgreen\x1b[0m\n"]), "green\n");
+        assert_eq!(render(&[b"\x1b]0;title\x07prompt"]), "prompt");
+        // Sequences may be split across reads too
+        assert_eq!(render(&[b"a\x1b[3", b"1mb"]), "ab");
+    }
+
+    #[test]
+    fn erase_to_end_of_line_truncates() {
+        assert_eq!(render(&[b"$ long command\r$ ls\x1b[K"]), "$ ls");
+    }
+
+    #[test]
+    fn tail_includes_the_incomplete_line() {
+        let mut terminal = Terminal::default();
+        terminal.feed(b"1\n2\n3\n4");
+        assert_eq!(terminal.tail(2), "3\n4");
+        assert_eq!(terminal.tail(10), "1\n2\n3\n4");
+    }
+
+    #[test]
+    fn old_lines_are_dropped() {
+        let mut terminal = Terminal::default();
+        for line in 0..MAX_LINES + 10 {
+            terminal.feed(format!("{line}\n").as_bytes());
+        }
+        assert!(terminal.text().starts_with("10\n"));
+    }
+}
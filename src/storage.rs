@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Disk space accounting for VMs and the filesystems holding them.
+
+use std::{os::unix::fs::MetadataExt, path::Path};
+
+use crate::{
+    qemu_img,
+    vm::{Vm, VmFileKind},
+};
+
+/// Space used by a VM, split by the kind of file it's used by.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VmUsage {
+    /// Space actually allocated on the host for disk images.
+    pub disk_allocated: u64,
+    /// The combined size of the disk images, as seen by the guest.
+    pub disk_virtual: u64,
+    pub installer_media: u64,
+    pub other: u64,
+}
+
+impl VmUsage {
+    pub fn total(&self) -> u64 {
+        self.disk_allocated + self.installer_media + self.other
+    }
+}
+
+pub fn vm_usage(vm: &Vm) -> Result<VmUsage, String> {
+    let mut usage = VmUsage::default();
+    for file in vm.files().map_err(|e| e.to_string())? {
+        match file.kind {
+            VmFileKind::DiskImage => {
+                // Sparse images take up less space than their length suggests
+                let allocated = std::fs::metadata(&file.path)
+                    .map(|metadata| metadata.blocks() * 512)
+                    .unwrap_or(file.size);
+                usage.disk_allocated += allocated;
+                usage.disk_virtual += qemu_img::info(&file.path)
+                    .map(|info| info.virtual_size)
+                    .unwrap_or(file.size);
+            }
+            VmFileKind::InstallerMedia => usage.installer_media += file.size,
+            VmFileKind::Config | VmFileKind::Firmware | VmFileKind::Other => {
+                usage.other += file.size
+            }
+        }
+    }
+    Ok(usage)
+}
+
+/// Space available to unprivileged users on the filesystem containing the given path.
+pub fn free_space(path: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).ok()?;
+    Some(stat.blocks_available() * stat.fragment_size())
+}
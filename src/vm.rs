@@ -0,0 +1,867 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    io::{Read, Seek, SeekFrom},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use quickemu_core::{
+    config::{
+        Config as QuickemuConfig, DiskImage, PortForward as QuickemuPortForward, PreAlloc,
+        Protocol as QuickemuProtocol,
+    },
+    data::{Arch, BootType},
+};
+use serde::{Deserialize, Serialize};
+
+/// Formats of disk images which can be used as they are, besides raw ones.
+const DISK_IMAGE_FORMATS: &[&str] = &["qcow2", "vmdk", "vdi", "vhdx", "vpc"];
+
+/// A VM registered in the app, backed by a quickemu config file.
+#[derive(Debug, Clone)]
+pub struct Vm {
+    config_path: PathBuf,
+    config: QuickemuConfig,
+}
+
+impl Vm {
+    pub fn load(config_path: PathBuf) -> Result<Self, String> {
+        let config = QuickemuConfig::parse(&config_path).map_err(|e| e.to_string())?;
+        Ok(Self {
+            config_path,
+            config,
+        })
+    }
+
+    /// Creates a VM around a local image rather than one downloaded by quickget. ISOs are
+    /// attached as installer media, and quickemu creates a blank disk for them on first launch.
+    /// Disk images are copied into the VM directory and booted directly. The config can be
+    /// adjusted with `customize` before it's saved.
+    pub fn create_from_image(
+        parent: &Path,
+        vm_name: &str,
+        image: &Path,
+        arch: Arch,
+        cpu_cores: usize,
+        ram: u64,
+        customize: impl FnOnce(&mut Vm),
+    ) -> Result<Vm, String> {
+        if !is_valid_name(parent, vm_name) {
+            return Err(format!("{vm_name} is not a valid VM name"));
+        }
+        let dir = parent.join(vm_name);
+        let config_path = parent.join(format!("{vm_name}.toml"));
+        if config_path.exists() {
+            return Err(format!("{} already exists", config_path.display()));
+        }
+
+        let mut vm = Vm {
+            config_path,
+            config: QuickemuConfig::default(),
+        };
+        vm.config.vm_name = vm_name.to_string();
+        vm.config.machine.arch = arch;
+        vm.set_cpu_cores(cpu_cores);
+        vm.set_ram(ram);
+
+        // Checked before anything is created, so that there's nothing to clean up
+        let kind = image_kind(image)?;
+
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Couldn't create {}: {e}", dir.display()))?;
+        let result = (|| {
+            match kind {
+                ImageKind::Iso => vm.set_installer_iso(Some(image.to_owned())),
+                ImageKind::Disk { format } => {
+                    // Named after the format, since raw images often have an ambiguous .img
+                    // extension
+                    let disk_name = format!("disk.{format}");
+                    copy_file(image, &dir.join(&disk_name))?;
+                    vm.config.disk_images = vec![DiskImage {
+                        path: PathBuf::from(disk_name),
+                        ..Default::default()
+                    }];
+                }
+            }
+            customize(&mut vm);
+            vm.save()
+        })();
+
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&vm.config_path);
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+        Ok(vm)
+    }
+
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    pub fn config(&self) -> &QuickemuConfig {
+        &self.config
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.vm_name
+    }
+
+    /// The directory holding the VM's disk images and runtime files. Quickemu places this next
+    /// to the config file, sharing its name, unless the config overrides it.
+    pub fn dir(&self) -> PathBuf {
+        self.config
+            .vm_dir
+            .clone()
+            .unwrap_or_else(|| self.config_path.with_extension(""))
+    }
+
+    pub fn cpu_cores(&self) -> Option<usize> {
+        self.config.machine.cpu_threads.map(|cores| cores.get())
+    }
+
+    pub fn ram(&self) -> Option<u64> {
+        self.config.machine.ram
+    }
+
+    pub fn set_cpu_cores(&mut self, cores: usize) {
+        self.config.machine.cpu_threads = NonZeroUsize::new(cores);
+    }
+
+    pub fn set_ram(&mut self, ram: u64) {
+        self.config.machine.ram = Some(ram);
+    }
+
+    pub fn uefi(&self) -> bool {
+        matches!(self.config.machine.boot, BootType::Efi { .. })
+    }
+
+    pub fn secure_boot(&self) -> bool {
+        matches!(
+            self.config.machine.boot,
+            BootType::Efi { secure_boot: true }
+        )
+    }
+
+    /// Switches between UEFI and legacy BIOS firmware. Secure Boot requires UEFI.
+    pub fn set_firmware(&mut self, uefi: bool, secure_boot: bool) {
+        self.config.machine.boot = if uefi {
+            BootType::Efi { secure_boot }
+        } else {
+            BootType::Legacy
+        };
+    }
+
+    pub fn set_tpm(&mut self, tpm: bool) {
+        self.config.machine.tpm = tpm;
+    }
+
+    /// The disk quickemu boots from, which is added if the config doesn't list any.
+    fn primary_disk_mut(&mut self) -> &mut DiskImage {
+        if self.config.disk_images.is_empty() {
+            self.config.disk_images.push(DiskImage {
+                path: PathBuf::from("disk.qcow2"),
+                ..Default::default()
+            });
+        }
+        &mut self.config.disk_images[0]
+    }
+
+    /// Sets the size of the primary disk. This only has an effect before quickemu creates it on
+    /// the VM's first launch.
+    pub fn set_disk_size(&mut self, size: u64) {
+        self.primary_disk_mut().size = Some(size);
+    }
+
+    pub fn set_preallocation(&mut self, preallocation: Preallocation) {
+        self.primary_disk_mut().preallocation = match preallocation {
+            Preallocation::Off => PreAlloc::Off,
+            Preallocation::Metadata => PreAlloc::Metadata,
+            Preallocation::Falloc => PreAlloc::Falloc,
+            Preallocation::Full => PreAlloc::Full,
+        };
+    }
+
+    /// The ISO the VM was installed from, attached as its first optical drive.
+    pub fn installer_iso(&self) -> Option<&Path> {
+        self.config.iso.as_deref()
+    }
+
+    pub fn set_installer_iso(&mut self, iso: Option<PathBuf>) {
+        self.config.iso = iso;
+    }
+
+    /// An extra ISO attached alongside the installer, such as a driver disk.
+    pub fn secondary_iso(&self) -> Option<&Path> {
+        self.config.fixed_iso.as_deref()
+    }
+
+    pub fn set_secondary_iso(&mut self, iso: Option<PathBuf>) {
+        self.config.fixed_iso = iso;
+    }
+
+    pub fn boot_order(&self) -> BootOrder {
+        match self.config.boot_order.as_deref() {
+            Some(order) if order.starts_with('c') => BootOrder::DiskFirst,
+            Some(order) if order.starts_with('d') => BootOrder::MediaFirst,
+            _ => BootOrder::Automatic,
+        }
+    }
+
+    pub fn set_boot_order(&mut self, boot_order: BootOrder) {
+        // QEMU names the first hard disk "c" and the first optical drive "d"
+        self.config.boot_order = match boot_order {
+            BootOrder::Automatic => None,
+            BootOrder::DiskFirst => Some("cd".to_string()),
+            BootOrder::MediaFirst => Some("dc".to_string()),
+        };
+    }
+
+    /// The host directory shared with the guest, if any.
+    pub fn shared_dir(&self) -> Option<&Path> {
+        self.config.io.public_dir.as_deref()
+    }
+
+    pub fn set_shared_dir(&mut self, dir: Option<PathBuf>) {
+        self.config.io.public_dir = dir;
+    }
+
+    pub fn shared_dir_mount_command(&self) -> Option<String> {
+        shared_dir_mount_command(&self.config.guest.to_string())
+    }
+
+    /// Ports forwarded from the host into the guest by QEMU's user-mode networking.
+    pub fn port_forwards(&self) -> Vec<PortForward> {
+        self.config
+            .network
+            .port_forwards
+            .iter()
+            .map(|forward| PortForward {
+                protocol: match forward.protocol {
+                    QuickemuProtocol::Tcp => Protocol::Tcp,
+                    QuickemuProtocol::Udp => Protocol::Udp,
+                },
+                host: forward.host,
+                guest: forward.guest,
+            })
+            .collect()
+    }
+
+    pub fn set_port_forwards(&mut self, forwards: &[PortForward]) {
+        self.config.network.port_forwards = forwards
+            .iter()
+            .map(|forward| QuickemuPortForward {
+                protocol: match forward.protocol {
+                    Protocol::Tcp => QuickemuProtocol::Tcp,
+                    Protocol::Udp => QuickemuProtocol::Udp,
+                },
+                host: forward.host,
+                guest: forward.guest,
+            })
+            .collect();
+    }
+
+    /// The config as it's written to the config file.
+    pub fn config_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(&self.config).map_err(|e| e.to_string())
+    }
+
+    /// Writes the config back to the file it was loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let config = self.config_toml()?;
+        std::fs::write(&self.config_path, config)
+            .map_err(|e| format!("Couldn't write {}: {e}", self.config_path.display()))
+    }
+
+    /// Moves the config file and VM directory into another parent directory, updating any paths
+    /// within the config that point into the old VM directory.
+    pub fn relocate(&mut self, parent: &Path) -> Result<(), String> {
+        let old_dir = self.dir();
+        let new_dir = parent.join(old_dir.file_name().unwrap_or_default());
+        let new_config_path = parent.join(self.config_path.file_name().unwrap_or_default());
+        if new_dir.exists() || new_config_path.exists() {
+            return Err(format!("{} already contains this VM", parent.display()));
+        }
+
+        move_path(&old_dir, &new_dir)?;
+        if let Err(e) = move_path(&self.config_path, &new_config_path) {
+            return Err(undo_moves(e, &[(old_dir.as_path(), new_dir.as_path())]));
+        }
+
+        let old_config_path = std::mem::replace(&mut self.config_path, new_config_path);
+        self.rebase_dir(&old_dir, &new_dir);
+        if let Err(e) = self.save() {
+            let e = undo_moves(
+                e,
+                &[
+                    (old_dir.as_path(), new_dir.as_path()),
+                    (old_config_path.as_path(), self.config_path.as_path()),
+                ],
+            );
+            self.config_path = old_config_path;
+            self.rebase_dir(&new_dir, &old_dir);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Copies the VM under a new name into the given parent directory. Linked clones get fresh
+    /// qcow2 overlays backed by this VM's disk images rather than full copies of them.
+    pub fn clone_to(&self, parent: &Path, vm_name: &str, mode: CloneMode) -> Result<Vm, String> {
+        if !is_valid_name(parent, vm_name) {
+            return Err(format!("{vm_name} is not a valid VM name"));
+        }
+        let old_dir = self.dir();
+        let new_dir = parent.join(vm_name);
+        let mut new_config_path = parent.join(vm_name);
+        if let Some(extension) = self.config_path.extension() {
+            new_config_path.set_extension(extension);
+        }
+        if new_config_path.exists() {
+            return Err(format!("{} already exists", new_config_path.display()));
+        }
+
+        let result = (|| {
+            for file in self.files().map_err(|e| e.to_string())? {
+                let Ok(relative) = file.path.strip_prefix(&old_dir) else {
+                    continue;
+                };
+                let destination = new_dir.join(relative);
+                if let Some(parent) = destination.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| format!("Couldn't create {}: {e}", parent.display()))?;
+                }
+
+                match (file.kind, mode) {
+                    (VmFileKind::DiskImage, CloneMode::Linked) => {
+                        let format = crate::qemu_img::info(&file.path)?.format;
+                        crate::qemu_img::create_overlay(&file.path, &format, &destination)?;
+                    }
+                    // Installer media is never written to, so it can be shared between VMs
+                    (VmFileKind::InstallerMedia, _) => {
+                        if std::fs::hard_link(&file.path, &destination).is_err() {
+                            copy_file(&file.path, &destination)?;
+                        }
+                    }
+                    _ => copy_file(&file.path, &destination)?,
+                }
+            }
+
+            let mut vm = Vm {
+                config_path: new_config_path.clone(),
+                config: self.config.clone(),
+            };
+            vm.config.vm_name = vm_name.to_string();
+            vm.rebase_dir(&old_dir, &new_dir);
+            vm.save()?;
+            Ok(vm)
+        })();
+
+        if result.is_err() {
+            // Don't leave a half copied VM behind
+            let _ = std::fs::remove_file(&new_config_path);
+            let _ = std::fs::remove_dir_all(&new_dir);
+        }
+        result
+    }
+
+    /// Points any paths within the config that lead into the old VM directory at the new one.
+    pub fn rebase_dir(&mut self, old_dir: &Path, new_dir: &Path) {
+        if self.config.vm_dir.is_some() {
+            self.config.vm_dir = Some(new_dir.to_owned());
+        }
+        let rebase = |path: &mut PathBuf| {
+            if let Ok(relative) = path.strip_prefix(old_dir) {
+                *path = new_dir.join(relative);
+            }
+        };
+        for disk in &mut self.config.disk_images {
+            rebase(&mut disk.path);
+        }
+        [
+            &mut self.config.iso,
+            &mut self.config.fixed_iso,
+            &mut self.config.io.public_dir,
+        ]
+        .into_iter()
+        .flatten()
+        .for_each(rebase);
+    }
+
+    /// The QMP socket the app asks QEMU to listen on, used to control the running VM.
+    pub fn qmp_socket(&self) -> PathBuf {
+        self.dir().join(format!("{}-qmp.socket", self.name()))
+    }
+
+    /// The socket quickemu exposes the guest's first serial port on.
+    pub fn serial_socket(&self) -> PathBuf {
+        self.dir().join(format!("{}-serial.socket", self.name()))
+    }
+
+    /// Starts QEMU with the arguments quickemu_core generates for this VM. Stderr is piped so
+    /// that errors can be surfaced in the UI.
+    pub fn spawn(&self) -> Result<tokio::process::Child, String> {
+        let command = self
+            .config
+            .clone()
+            .to_command()
+            .map_err(|e| e.to_string())?;
+
+        tokio::process::Command::from(command)
+            .arg("-qmp")
+            .arg(format!(
+                "unix:{},server,nowait",
+                self.qmp_socket().display()
+            ))
+            .current_dir(self.dir())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Couldn't start QEMU: {e}"))
+    }
+
+    /// The VM's disk images, with paths resolved against the VM directory.
+    pub fn disk_images(&self) -> Vec<PathBuf> {
+        let dir = self.dir();
+        self.config
+            .disk_images
+            .iter()
+            .map(|disk| dir.join(&disk.path))
+            .collect()
+    }
+
+    /// Lists every file belonging to the VM, including its config.
+    pub fn files(&self) -> std::io::Result<Vec<VmFile>> {
+        let dir = self.dir();
+        let disk_images = self.disk_images();
+
+        let mut paths = vec![];
+        if dir.is_dir() {
+            walk_dir(&dir, &mut paths)?;
+        }
+
+        let mut files = vec![VmFile {
+            size: std::fs::metadata(&self.config_path)?.len(),
+            path: self.config_path.clone(),
+            kind: VmFileKind::Config,
+        }];
+        for path in paths {
+            let kind = if disk_images.contains(&path) {
+                VmFileKind::DiskImage
+            } else {
+                VmFileKind::from_path(&path)
+            };
+            files.push(VmFile {
+                size: std::fs::metadata(&path)?.len(),
+                path,
+                kind,
+            });
+        }
+        Ok(files)
+    }
+}
+
+/// What a custom image can be used as.
+enum ImageKind {
+    Iso,
+    Disk { format: String },
+}
+
+/// Works out what a custom image is, refusing files which are neither ISOs nor disk images.
+fn image_kind(image: &Path) -> Result<ImageKind, String> {
+    let unrecognised = || format!("{} isn't an ISO or a disk image", image.display());
+    let extension = image
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+
+    if extension.as_deref() == Some("iso") {
+        // ISO 9660 filesystems carry this identifier at the start of their first volume
+        // descriptor
+        let mut identifier = [0; 5];
+        let read = std::fs::File::open(image).and_then(|mut file| {
+            file.seek(SeekFrom::Start(0x8001))?;
+            file.read_exact(&mut identifier)
+        });
+        return match read {
+            Ok(()) if &identifier == b"CD001" => Ok(ImageKind::Iso),
+            Ok(()) | Err(_) => Err(unrecognised()),
+        };
+    }
+
+    let format = crate::qemu_img::info(image)?.format;
+    let recognised = match format.as_str() {
+        // qemu-img reports anything it doesn't recognise as raw, so the extension has to say
+        "raw" => matches!(extension.as_deref(), Some("img" | "raw")),
+        format => DISK_IMAGE_FORMATS.contains(&format),
+    };
+    if recognised {
+        Ok(ImageKind::Disk { format })
+    } else {
+        Err(unrecognised())
+    }
+}
+
+/// Whether a new VM with the given name may be created within the parent directory.
+pub fn is_valid_name(parent: &Path, vm_name: &str) -> bool {
+    name_issue(parent, vm_name).is_none()
+}
+
+/// Why a VM can't be created under the name in the parent directory, if it can't.
+pub fn name_issue(parent: &Path, vm_name: &str) -> Option<String> {
+    if vm_name.is_empty() {
+        Some("Enter a name for the VM".to_string())
+    } else if vm_name.contains('/') {
+        Some("VM names can't contain '/'".to_string())
+    } else if !parent.is_dir() {
+        Some(format!("{} doesn't exist", parent.display()))
+    } else if parent.join(vm_name).exists() {
+        Some(format!("{vm_name} already exists in {}", parent.display()))
+    } else if parent.join(format!("{vm_name}.toml")).exists() {
+        Some(format!(
+            "{vm_name}.toml already exists in {}",
+            parent.display()
+        ))
+    } else {
+        None
+    }
+}
+
+/// The name itself if it's free in the parent directory, otherwise the first free name with a
+/// number appended, e.g. "ubuntu-24.04-x86_64-2".
+pub fn unique_name(parent: &Path, vm_name: &str) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => vm_name.to_string(),
+            n => format!("{vm_name}-{n}"),
+        })
+        .find(|name| !parent.join(name).exists() && !parent.join(format!("{name}.toml")).exists())
+        .expect("A free name should always be found")
+}
+
+/// The command mounting the shared directory within a guest, where quickemu exposes it over 9p.
+/// Windows guests reach it through the SPICE WebDAV daemon instead, so have no command.
+pub fn shared_dir_mount_command(guest: &str) -> Option<String> {
+    let guest = guest.to_lowercase();
+    // Quickemu tags the share with the host user's name
+    let user = std::env::var("USER").unwrap_or_default().to_lowercase();
+    if guest.contains("windows") {
+        None
+    } else if guest.contains("macos") {
+        Some(format!("sudo mount_9p Public-{user}"))
+    } else {
+        Some(format!(
+            "mkdir -p ~/Public && sudo mount -t 9p -o trans=virtio,version=9p2000.L,msize=104857600 \
+             Public-{user} ~/Public"
+        ))
+    }
+}
+
+/// Moves a file or directory, falling back to copying and deleting it when the destination is
+/// on another filesystem.
+fn move_path(source: &Path, destination: &Path) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("Couldn't move {}: {e}", source.display());
+    match std::fs::rename(source, destination) {
+        Err(e) if e.raw_os_error() == Some(nix::errno::Errno::EXDEV as i32) => {
+            let copied = if source.is_dir() {
+                copy_dir(source, destination)
+            } else {
+                std::fs::copy(source, destination).map(|_| ())
+            };
+            if let Err(e) = copied {
+                _ = remove_path(destination);
+                return Err(error(e));
+            }
+            remove_path(source).map_err(error)
+        }
+        result => result.map_err(error),
+    }
+}
+
+/// Moves back everything that was moved before a failure, given as (original, moved to) pairs,
+/// and describes the failure.
+fn undo_moves(error: String, moves: &[(&Path, &Path)]) -> String {
+    moves.iter().rev().fold(error, |error, (original, moved)| {
+        match move_path(moved, original) {
+            Ok(()) => error,
+            Err(e) => format!(
+                "{error}. Undoing that also failed, leaving {}: {e}",
+                moved.display()
+            ),
+        }
+    })
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> std::io::Result<()> {
+    std::fs::create_dir(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = destination.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+fn copy_file(source: &Path, destination: &Path) -> Result<(), String> {
+    std::fs::copy(source, destination)
+        .map(|_| ())
+        .map_err(|e| format!("Couldn't copy {}: {e}", source.display()))
+}
+
+fn walk_dir(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            walk_dir(&entry.path(), paths)?;
+        } else if file_type.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloneMode {
+    /// Copies every disk image in full.
+    Full,
+    /// Creates qcow2 overlays which use the original disk images as their backing files.
+    Linked,
+}
+
+/// How much of a new qcow2 disk is allocated on the host up front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Preallocation {
+    /// Space is allocated as the guest writes to the disk.
+    #[default]
+    Off,
+    /// Only qcow2's own metadata is allocated, which speeds up the disk's first writes.
+    Metadata,
+    /// The whole disk is reserved without writing to it.
+    Falloc,
+    /// The whole disk is written out with zeros.
+    Full,
+}
+
+impl Preallocation {
+    /// Whether the full size of the disk is taken from the host when it's created.
+    pub fn reserves_space(self) -> bool {
+        matches!(self, Self::Falloc | Self::Full)
+    }
+}
+
+impl std::fmt::Display for Preallocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Metadata => "Metadata",
+            Self::Falloc => "Falloc",
+            Self::Full => "Full",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootOrder {
+    /// Left to quickemu, which boots from the installer while one is attached.
+    Automatic,
+    DiskFirst,
+    MediaFirst,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortForward {
+    pub protocol: Protocol,
+    pub host: u16,
+    pub guest: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Tcp => "TCP",
+            Self::Udp => "UDP",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VmFile {
+    pub path: PathBuf,
+    pub kind: VmFileKind,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmFileKind {
+    Config,
+    DiskImage,
+    InstallerMedia,
+    /// UEFI variable stores and emulated TPM state.
+    Firmware,
+    Other,
+}
+
+impl VmFileKind {
+    fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        let in_tpm_dir = path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|dir| dir.to_string_lossy().contains("tpm"));
+        match path.extension().and_then(|ext| ext.to_str()) {
+            _ if name.contains("ovmf") || name.contains("vars") || in_tpm_dir => Self::Firmware,
+            Some("fd") => Self::Firmware,
+            Some("iso" | "img" | "dmg") => Self::InstallerMedia,
+            Some("qcow2" | "raw" | "vmdk" | "vdi" | "vhdx") => Self::DiskImage,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Vm {
+    /// A VM named after its config file, with quickemu's default config. Nothing is written.
+    pub(crate) fn with_default_config(config_path: PathBuf) -> Self {
+        let mut config = QuickemuConfig::default();
+        config.vm_name = config_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        Self {
+            config_path,
+            config,
+        }
+    }
+}
+
+impl std::fmt::Display for VmFileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Config => "Config",
+            Self::DiskImage => "Disk image",
+            Self::InstallerMedia => "Installer media",
+            Self::Firmware => "Firmware state",
+            Self::Other => "Other",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn rebase_dir_only_moves_paths_inside_the_vm_dir() {
+        let mut vm = Vm {
+            config_path: PathBuf::from("/old/vm.toml"),
+            config: QuickemuConfig::default(),
+        };
+        vm.config.vm_dir = Some(PathBuf::from("/old/vm"));
+        vm.config.iso = Some(PathBuf::from("/old/vm/installer.iso"));
+        vm.config.fixed_iso = Some(PathBuf::from("/elsewhere/drivers.iso"));
+        vm.config.io.public_dir = Some(PathBuf::from("/old/vm/shared"));
+        vm.config.disk_images = vec![DiskImage {
+            path: PathBuf::from("/old/vm/disk.qcow2"),
+            ..Default::default()
+        }];
+
+        vm.rebase_dir(Path::new("/old/vm"), Path::new("/new/vm"));
+
+        assert_eq!(vm.dir(), Path::new("/new/vm"));
+        assert_eq!(vm.installer_iso(), Some(Path::new("/new/vm/installer.iso")));
+        assert_eq!(
+            vm.secondary_iso(),
+            Some(Path::new("/elsewhere/drivers.iso"))
+        );
+        assert_eq!(vm.shared_dir(), Some(Path::new("/new/vm/shared")));
+        assert_eq!(
+            vm.config.disk_images[0].path,
+            Path::new("/new/vm/disk.qcow2")
+        );
+    }
+
+    #[test]
+    fn clone_points_at_its_own_installer_iso() {
+        let parent = temp_dir("clone");
+        let old_dir = parent.join("vm");
+        std::fs::create_dir_all(&old_dir).unwrap();
+        std::fs::write(old_dir.join("installer.iso"), b"iso").unwrap();
+
+        let mut vm = Vm {
+            config_path: parent.join("vm.toml"),
+            config: QuickemuConfig::default(),
+        };
+        vm.config.vm_name = "vm".to_string();
+        vm.set_installer_iso(Some(old_dir.join("installer.iso")));
+        vm.save().unwrap();
+
+        let clone = vm.clone_to(&parent, "clone", CloneMode::Full).unwrap();
+        let iso = parent.join("clone").join("installer.iso");
+        assert_eq!(clone.installer_iso(), Some(iso.as_path()));
+        assert!(iso.is_file());
+        assert_eq!(
+            vm.installer_iso(),
+            Some(old_dir.join("installer.iso").as_path())
+        );
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+
+    #[test]
+    fn name_issue_explains_invalid_names() {
+        let parent = temp_dir("name-issue");
+        std::fs::create_dir(parent.join("taken")).unwrap();
+        std::fs::write(parent.join("configured.toml"), "").unwrap();
+
+        assert!(name_issue(&parent, "free").is_none());
+        assert!(name_issue(&parent, "").is_some());
+        assert!(name_issue(&parent, "a/b").is_some());
+        assert!(name_issue(&parent, "taken").is_some());
+        assert!(name_issue(&parent, "configured").is_some());
+        assert!(name_issue(&parent.join("missing"), "free").is_some());
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+
+    #[test]
+    fn unique_name_appends_the_first_free_number() {
+        let parent = temp_dir("unique-name");
+        assert_eq!(unique_name(&parent, "vm"), "vm");
+
+        std::fs::create_dir(parent.join("vm")).unwrap();
+        std::fs::write(parent.join("vm-2.toml"), "").unwrap();
+        assert_eq!(unique_name(&parent, "vm"), "vm-3");
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+}